@@ -0,0 +1,185 @@
+// Reference indexer for `ClaimYield` events. Every settled claim logs
+// `ClaimYield <tokenizer> <user> <amount>`, including claims made through
+// `RedeemPrincipalAndYield` and `BatchRedeem`, so totalling the events over the program's
+// transactions gives the yield claimed from each tokenizer.
+//
+// cargo run --example indexer -- [RPC_URL]
+use {
+	anyhow::Result,
+	solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig},
+	solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature},
+	std::{collections::BTreeMap, str::FromStr},
+};
+
+const EVENT_PREFIX: &str = "Program log: ClaimYield ";
+
+// Extracts `(tokenizer, user, amount)` from the claim events in a transaction's logs. Only
+// lines logged while the tokenizer program itself is executing count, so another program in
+// the same transaction cannot forge an event by logging the same text.
+fn claim_events(log_messages: &[String]) -> Vec<(Pubkey, Pubkey, u64)> {
+	let program_id = sclr_token::id().to_string();
+	let mut call_stack: Vec<&str> = Vec::new();
+	let mut events = Vec::new();
+
+	for line in log_messages {
+		if let Some(event) = line.strip_prefix(EVENT_PREFIX) {
+			if call_stack.last() != Some(&program_id.as_str()) {
+				continue;
+			}
+
+			let fields: Vec<&str> = event.split(' ').collect();
+			if let [tokenizer, user, amount] = fields[..] {
+				if let (Ok(tokenizer), Ok(user), Ok(amount)) = (
+					Pubkey::from_str(tokenizer),
+					Pubkey::from_str(user),
+					amount.parse(),
+				) {
+					events.push((tokenizer, user, amount));
+				}
+			}
+			continue;
+		}
+
+		// Program logs are the only lines a program controls, the rest are written by the
+		// runtime and track which program is executing
+		if line.starts_with("Program log: ") || line.starts_with("Program data: ") {
+			continue;
+		}
+
+		let words: Vec<&str> = line.split(' ').collect();
+		match words[..] {
+			["Program", id, "invoke", ..] => call_stack.push(id),
+			["Program", _, "success"] | ["Program", _, "failed:", ..] => {
+				call_stack.pop();
+			}
+			_ => {}
+		}
+	}
+
+	events
+}
+
+// Adds a transaction's claim events to the yield claimed from each tokenizer
+fn add_claims(claimed: &mut BTreeMap<Pubkey, u64>, log_messages: &[String]) {
+	for (tokenizer, _, amount) in claim_events(log_messages) {
+		let total = claimed.entry(tokenizer).or_default();
+		*total = total.saturating_add(amount);
+	}
+}
+
+fn main() -> Result<()> {
+	let rpc_url = std::env::args()
+		.nth(1)
+		.unwrap_or_else(|| "http://localhost:8899".to_string());
+	let client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+	let config = RpcTransactionConfig {
+		commitment: Some(CommitmentConfig::confirmed()),
+		max_supported_transaction_version: Some(0),
+		..RpcTransactionConfig::default()
+	};
+
+	// Only the most recent page of signatures is fetched, enough for a local validator
+	let mut claimed: BTreeMap<Pubkey, u64> = BTreeMap::new();
+	for status in client.get_signatures_for_address(&sclr_token::id())? {
+		if status.err.is_some() {
+			continue;
+		}
+
+		let signature = Signature::from_str(&status.signature)?;
+		let transaction = client.get_transaction_with_config(&signature, config)?;
+		let log_messages = transaction
+			.transaction
+			.meta
+			.map(|meta| meta.log_messages.unwrap_or_else(Vec::new))
+			.unwrap_or_default();
+
+		add_claims(&mut claimed, &log_messages);
+	}
+
+	for (tokenizer, amount) in claimed {
+		println!("{} {}", tokenizer, amount);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+	const TOKENIZER: &str = "8DcMsLouCXpbGksNNnfF1cmfe1Nr2Kkx4qCQHQBSgUrq";
+	const USER: &str = "D3oTwaZBzqi9swArY2MwRpgexQAZBTSVsYzs8JDogRBH";
+
+	// The log messages of a transaction's metadata for a `ClaimYield` paying out `amount`
+	fn claim_logs(tokenizer: &str, amount: u64) -> Vec<String> {
+		let program_id = sclr_token::id().to_string();
+
+		vec![
+			format!("Program {} invoke [1]", program_id),
+			"Program log: Claiming yield...".to_string(),
+			format!("Program {} invoke [2]", TOKEN_PROGRAM),
+			"Program log: Instruction: Burn".to_string(),
+			format!(
+				"Program {} consumed 4707 of 199859 compute units",
+				TOKEN_PROGRAM
+			),
+			format!("Program {} success", TOKEN_PROGRAM),
+			format!("Program {} invoke [2]", TOKEN_PROGRAM),
+			"Program log: Instruction: Transfer".to_string(),
+			format!(
+				"Program {} consumed 4645 of 195152 compute units",
+				TOKEN_PROGRAM
+			),
+			format!("Program {} success", TOKEN_PROGRAM),
+			format!("Program log: ClaimYield {} {} {}", tokenizer, USER, amount),
+			format!(
+				"Program {} consumed 21394 of 200000 compute units",
+				program_id
+			),
+			format!("Program {} success", program_id),
+		]
+	}
+
+	#[test]
+	fn claim_events_reads_the_claim() {
+		assert_eq!(
+			claim_events(&claim_logs(TOKENIZER, 50)),
+			[(
+				Pubkey::from_str(TOKENIZER).unwrap(),
+				Pubkey::from_str(USER).unwrap(),
+				50
+			)]
+		);
+	}
+
+	#[test]
+	fn claim_events_ignores_other_programs() {
+		let mut log_messages = claim_logs(TOKENIZER, 50);
+		log_messages.extend([
+			format!("Program {} invoke [1]", TOKEN_PROGRAM),
+			format!("Program log: ClaimYield {} {} 1000", TOKENIZER, USER),
+			format!("Program {} success", TOKEN_PROGRAM),
+		]);
+
+		assert_eq!(claim_events(&log_messages).len(), 1);
+	}
+
+	#[test]
+	fn add_claims_totals_each_tokenizer() {
+		let other = Pubkey::new_unique().to_string();
+		let mut claimed = BTreeMap::new();
+
+		add_claims(&mut claimed, &claim_logs(TOKENIZER, 50));
+		add_claims(&mut claimed, &claim_logs(&other, 7));
+		add_claims(&mut claimed, &claim_logs(TOKENIZER, 25));
+
+		assert_eq!(
+			claimed,
+			BTreeMap::from([
+				(Pubkey::from_str(TOKENIZER).unwrap(), 75),
+				(Pubkey::from_str(&other).unwrap(), 7),
+			])
+		);
+	}
+}
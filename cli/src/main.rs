@@ -1,18 +1,28 @@
 use {
 	anyhow::{anyhow, Result},
-	clap::{command, Args, Parser, Subcommand},
+	borsh::BorshDeserialize,
+	clap::{Args, Parser, Subcommand},
 	sclr_token::{
-		get_principal_mint_address, get_tokenizer_address, get_yield_mint_address, instruction,
+		derive_tokenizer, expiry_label, get_lp_mint_address, get_pool_address,
+		get_principal_mint_address, get_yield_mint_address, init_rent, instruction, mul_div_floor,
+		pool::{swap_out, PoolState},
+		state::{TokenizerState, STATE_SIZE},
 		Expiry,
 	},
-	solana_cli_config,
-	solana_client::rpc_client::RpcClient,
-	solana_program::{instruction::Instruction, pubkey::Pubkey},
+	solana_account_decoder::UiAccountEncoding,
+	solana_client::{
+		rpc_client::RpcClient,
+		rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+		rpc_filter::RpcFilterType,
+	},
+	solana_program::{instruction::Instruction, program_pack::Pack, pubkey::Pubkey, rent::Rent},
 	solana_sdk::{
 		commitment_config::CommitmentConfig,
 		signature::{read_keypair_file, Signer},
 		transaction::Transaction,
 	},
+	spl_token::state::{Account as TokenAccount, Mint},
+	std::io::Write,
 };
 
 #[derive(Parser, Debug)]
@@ -39,14 +49,63 @@ enum Commands {
 	Terminate(Terminate),
 	#[command(subcommand)]
 	Swap(Swap),
+	Info(InfoFields),
+	/// List every tokenizer owned by the program
+	List,
+	#[command(subcommand)]
+	Config(Config),
+	#[cfg(feature = "dev")]
+	#[command(subcommand)]
+	Dev(Dev),
+}
+
+impl Commands {
+	// The existing tokenizer a command operates on, if any
+	fn tokenizer_address(&self) -> Option<Pubkey> {
+		match self {
+			Commands::Tokenize(
+				Tokenize::Deposit(fields)
+				| Tokenize::Principal(fields)
+				| Tokenize::Yield(fields)
+				| Tokenize::PrincipalYield(fields),
+			) => Some(fields.lysergic_tokenizer_address),
+			Commands::Redeem(Redeem::Principal(fields)) => {
+				Some(fields.common.lysergic_tokenizer_address)
+			}
+			Commands::Redeem(Redeem::Yield(fields) | Redeem::PrincipalYield(fields)) => {
+				Some(fields.lysergic_tokenizer_address)
+			}
+			Commands::Info(fields) => Some(fields.lysergic_tokenizer_address),
+			_ => None,
+		}
+	}
 }
 
+#[derive(Debug)]
+enum CliError {
+	TokenizerNotFound(Pubkey),
+}
+
+impl std::fmt::Display for CliError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			CliError::TokenizerNotFound(address) => write!(
+				f,
+				"Tokenizer {} not found, check the underlying mint and expiry it was derived from",
+				address
+			),
+		}
+	}
+}
+
+impl std::error::Error for CliError {}
+
 #[derive(Subcommand, Debug)]
 enum Initialize {
 	Tokenizer(InitializeCommonFields),
 	Mints(InitializeCommonFields),
 	TokenizerMints(InitializeCommonFields),
-	Amm(InitializeCommonFields),
+	Amm(InitializeAmmFields),
 }
 
 #[derive(Subcommand, Debug)]
@@ -59,42 +118,604 @@ enum Tokenize {
 
 #[derive(Subcommand, Debug)]
 enum Redeem {
-	Principal(InstructionCommonFields),
+	Principal(RedeemPrincipalFields),
 	Yield(InstructionCommonFields),
 	PrincipalYield(InstructionCommonFields),
 }
 
 #[derive(Subcommand, Debug)]
+#[allow(clippy::enum_variant_names)]
 enum Terminate {
-	Terminate(TerminateCommonFields),
+	Terminate(TerminateFields),
 	TerminateTokenizer(TerminateCommonFields),
 	TerminateMints(TerminateCommonFields),
 }
 
 #[derive(Subcommand, Debug)]
 enum Swap {
-	Principal(InstructionCommonFields),
+	/// Swap principal for underlying against the tokenizer's pool
+	Principal(SwapFields),
+	/// Not supported, the pool only trades principal against underlying
 	Yield(InstructionCommonFields),
-	Underlying(InstructionCommonFields),
+	/// Swap underlying for principal against the tokenizer's pool
+	Underlying(SwapFields),
 }
 
 #[derive(Args, Debug)]
 struct InitializeCommonFields {
 	underlying_mint_address: Pubkey,
+	/// Tenor in months; anything but 12, 18 or 24 is a custom tenor, which the program
+	/// config must allow
 	expiry: i64,
 }
 
+#[derive(Args, Debug)]
+struct InitializeAmmFields {
+	#[command(flatten)]
+	common: InitializeCommonFields,
+	/// Fee charged on the input of each swap, in basis points
+	#[arg(long, default_value_t = 30)]
+	fee_bps: u16,
+}
+
 #[derive(Args, Debug)]
 struct InstructionCommonFields {
 	lysergic_tokenizer_address: Pubkey,
-	amount: u64,
+	/// Amount in raw base units of the token
+	#[arg(
+		long,
+		required_unless_present = "ui_amount",
+		conflicts_with = "ui_amount"
+	)]
+	amount: Option<u64>,
+	/// Amount in whole tokens, converted using the underlying mint's decimals
+	#[arg(long)]
+	ui_amount: Option<String>,
 	underlying_mint_address: Pubkey,
+	/// Receive native SOL rather than wrapped SOL when redeeming
+	#[arg(long)]
+	unwrap: bool,
+}
+
+impl InstructionCommonFields {
+	fn amount(&self, client: &RpcClient) -> Result<u64> {
+		match (self.amount, &self.ui_amount) {
+			(Some(amount), _) => Ok(amount),
+			(None, Some(ui_amount)) => {
+				let decimals = get_mint_decimals(client, &self.underlying_mint_address)?;
+				ui_amount_to_base_units(ui_amount, decimals)
+			}
+			(None, None) => Err(anyhow!("Either `--amount` or `--ui-amount` is required")),
+		}
+	}
+}
+
+#[derive(Args, Debug)]
+struct SwapFields {
+	#[command(flatten)]
+	common: InstructionCommonFields,
+	/// How far the output may fall short of the quote, in basis points
+	#[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u16).range(0..=10_000))]
+	slippage_bps: u16,
+}
+
+#[derive(Args, Debug)]
+struct RedeemPrincipalFields {
+	#[command(flatten)]
+	common: InstructionCommonFields,
+	/// Close the principal token account afterwards, requires redeeming the full balance
+	#[arg(long)]
+	close_principal_ata: bool,
 }
 
 #[derive(Args, Debug)]
 struct TerminateCommonFields {
 	lysergic_tokenizer_address: Pubkey,
 	underlying_mint_address: Pubkey,
+	/// Skip the confirmation prompt
+	#[arg(long)]
+	yes: bool,
+}
+
+#[derive(Args, Debug)]
+struct TerminateFields {
+	#[command(flatten)]
+	common: TerminateCommonFields,
+	/// Terminate the mints and the tokenizer in two transactions instead of one
+	#[arg(long)]
+	split: bool,
+}
+
+impl TerminateCommonFields {
+	// Terminating closes accounts irreversibly, so ask before sending unless `--yes` is given
+	fn confirm(&self) -> Result<()> {
+		println!(
+			"WARNING: this will irreversibly close accounts of tokenizer {}",
+			self.lysergic_tokenizer_address
+		);
+
+		if self.yes {
+			return Ok(());
+		}
+
+		print!("Are you sure? [y/N] ");
+		std::io::stdout().flush()?;
+
+		let mut answer = String::new();
+		std::io::stdin().read_line(&mut answer)?;
+
+		match answer.trim() {
+			"y" | "Y" | "yes" => Ok(()),
+			_ => Err(anyhow!("Aborted")),
+		}
+	}
+}
+
+#[derive(Args, Debug)]
+struct InfoFields {
+	lysergic_tokenizer_address: Pubkey,
+}
+
+#[derive(Subcommand, Debug)]
+enum Config {
+	/// Create the program config, must be signed by the program's upgrade authority
+	Init(ConfigFields),
+	/// Update the range of tenors new tokenizers may be created with
+	Update(ConfigFields),
+}
+
+#[derive(Args, Debug)]
+struct ConfigFields {
+	/// Shortest tenor in months
+	#[arg(long, default_value_t = 12)]
+	min_tenor: i64,
+	/// Longest tenor in months
+	#[arg(long, default_value_t = 24)]
+	max_tenor: i64,
+}
+
+#[cfg(feature = "dev")]
+#[derive(Subcommand, Debug)]
+enum Dev {
+	/// Create and fund a new underlying mint, then initialize a 12 month tokenizer for it. The
+	/// program config is created first if it does not exist yet, which requires the wallet to be
+	/// the program's upgrade authority.
+	Setup(DevSetupFields),
+}
+
+#[cfg(feature = "dev")]
+#[derive(Args, Debug)]
+struct DevSetupFields {
+	#[arg(long, default_value_t = 6)]
+	decimals: u8,
+	/// Amount in raw base units to mint to the wallet
+	amount: u64,
+}
+
+fn get_mint_decimals(client: &RpcClient, mint_address: &Pubkey) -> Result<u8> {
+	let mint_data = client
+		.get_account_data(mint_address)
+		.map_err(|err| anyhow!("Unable to fetch mint {}: {}", mint_address, err))?;
+
+	Ok(Mint::unpack(&mint_data)
+		.map_err(|err| anyhow!("Unable to decode mint {}: {}", mint_address, err))?
+		.decimals)
+}
+
+// Converts a decimal string such as `1.5` into base units without going through a float,
+// rejecting inputs with more decimal places than the mint supports
+fn ui_amount_to_base_units(ui_amount: &str, decimals: u8) -> Result<u64> {
+	let (whole, fraction) = ui_amount.split_once('.').unwrap_or((ui_amount, ""));
+
+	if fraction.len() > decimals as usize {
+		return Err(anyhow!(
+			"`{}` has more than {} decimal places",
+			ui_amount,
+			decimals
+		));
+	}
+
+	let digits = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+	if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+		return Err(anyhow!("`{}` is not a valid amount", ui_amount));
+	}
+
+	digits
+		.parse::<u64>()
+		.map_err(|err| anyhow!("`{}` is not a valid amount: {}", ui_amount, err))
+}
+
+// Inverse of `ui_amount_to_base_units`, e.g. `1000000` with 6 decimals is `1.000000`
+fn base_units_to_ui_amount(amount: u64, decimals: u8) -> String {
+	let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+	let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+
+	if fraction.is_empty() {
+		whole.to_string()
+	} else {
+		format!("{}.{}", whole, fraction)
+	}
+}
+
+// Prints the balance of one of the wallet's tokenizer token accounts, in whole tokens
+// alongside the raw base units
+fn print_token_balance(
+	client: &RpcClient,
+	symbol: &str,
+	mint_address: &Pubkey,
+	token_account_address: &Pubkey,
+) -> Result<()> {
+	let decimals = get_mint_decimals(client, mint_address)?;
+	let token_account_data = client
+		.get_account_data(token_account_address)
+		.map_err(|err| anyhow!("Unable to fetch account {}: {}", token_account_address, err))?;
+	let amount = TokenAccount::unpack(&token_account_data)
+		.map_err(|err| {
+			anyhow!(
+				"Unable to decode account {}: {}",
+				token_account_address,
+				err
+			)
+		})?
+		.amount;
+
+	println!(
+		"{} Balance: {} {} ({})",
+		symbol,
+		base_units_to_ui_amount(amount, decimals),
+		symbol,
+		amount
+	);
+
+	Ok(())
+}
+
+// Fails with `CliError::TokenizerNotFound` up front rather than deep inside the RPC call or
+// transaction that would otherwise trip over the missing account
+fn ensure_tokenizer_exists(client: &RpcClient, tokenizer_address: &Pubkey) -> Result<()> {
+	let account = client
+		.get_account_with_commitment(tokenizer_address, client.commitment())
+		.map_err(|err| anyhow!("Unable to fetch tokenizer {}: {}", tokenizer_address, err))?
+		.value;
+
+	if account.is_none() {
+		return Err(CliError::TokenizerNotFound(*tokenizer_address).into());
+	}
+
+	Ok(())
+}
+
+// Fails before sending if the payer cannot cover the rent of every account a tokenizer
+// initialization creates, which would otherwise only surface as a failed create_account
+fn ensure_payer_can_fund_init(client: &RpcClient, payer: &Pubkey) -> Result<()> {
+	let rent_account = client
+		.get_account(&solana_sdk::sysvar::rent::id())
+		.map_err(|err| anyhow!("Unable to fetch the rent sysvar: {}", err))?;
+	let rent: Rent = solana_sdk::account::from_account(&rent_account)
+		.ok_or_else(|| anyhow!("Unable to decode the rent sysvar"))?;
+
+	let required = init_rent(&rent, &spl_token::id())?;
+	let balance = client.get_balance(payer)?;
+
+	if balance < required {
+		return Err(anyhow!(
+			"Payer {} holds {} lamports but initializing a tokenizer needs at least {} for rent",
+			payer,
+			balance,
+			required
+		));
+	}
+
+	Ok(())
+}
+
+// A layout mismatch almost always means the program was upgraded without the client (or
+// vice versa), so say that rather than surfacing the raw Borsh error
+fn decode_tokenizer_state(data: &[u8]) -> Result<TokenizerState> {
+	if data.len() != STATE_SIZE {
+		return Err(anyhow!(
+			"Tokenizer account holds {} bytes but this client expects {}; \
+			 the program and client versions likely differ",
+			data.len(),
+			STATE_SIZE
+		));
+	}
+
+	TokenizerState::try_from_slice(data).map_err(|err| {
+		anyhow!(
+			"Unable to decode tokenizer state ({}); the program and client versions likely differ",
+			err
+		)
+	})
+}
+
+// Every account owned by the program with the tokenizer state's size, skipping any that do
+// not decode as a live tokenizer
+fn all_tokenizers(client: &RpcClient) -> Result<Vec<(Pubkey, TokenizerState)>> {
+	let accounts = client
+		.get_program_accounts_with_config(
+			&sclr_token::id(),
+			RpcProgramAccountsConfig {
+				filters: Some(vec![RpcFilterType::DataSize(STATE_SIZE as u64)]),
+				account_config: RpcAccountInfoConfig {
+					encoding: Some(UiAccountEncoding::Base64),
+					..RpcAccountInfoConfig::default()
+				},
+				..RpcProgramAccountsConfig::default()
+			},
+		)
+		.map_err(|err| anyhow!("Unable to fetch tokenizer accounts: {}", err))?;
+
+	Ok(accounts
+		.into_iter()
+		.filter_map(|(address, account)| {
+			TokenizerState::unpack_initialized(&account.data)
+				.ok()
+				.map(|state| (address, state))
+		})
+		.collect())
+}
+
+fn print_tokenizer_info(client: &RpcClient, tokenizer_address: &Pubkey) -> Result<()> {
+	let data = client
+		.get_account_data(tokenizer_address)
+		.map_err(|err| anyhow!("Unable to fetch tokenizer {}: {}", tokenizer_address, err))?;
+	let state = decode_tokenizer_state(&data)?;
+
+	println!("Tokenizer Address: {}", tokenizer_address);
+	println!("Authority: {}", state.authority);
+	println!("Underlying Mint Address: {}", state.underlying_mint);
+	println!("Vault Address: {}", state.underlying_vault);
+	println!("Principal Mint Address: {}", state.principal_token_mint);
+	println!("Yield Mint Address: {}", state.yield_token_mint);
+	println!("Token Program: {}", state.token_program);
+	println!(
+		"Expiry Date: {} ({})",
+		expiry_label(state.expiry_date),
+		state.expiry_date
+	);
+	println!("Fixed APY (bps): {}", state.fixed_apy);
+	println!("Total Deposited: {}", state.total_deposited);
+	println!("Mints Initialized: {}", state.mints_initialized);
+	println!("Mints Terminated: {}", state.mints_terminated);
+	println!("Minimum Hold (s): {}", state.min_hold_seconds);
+	println!("Rebasing: {}", state.rebasing);
+
+	Ok(())
+}
+
+// Quotes a swap against the tokenizer's pool from its tracked reserves, then builds it with
+// the least output the slippage allows
+fn swap_instruction(
+	client: &RpcClient,
+	wallet_pubkey: &Pubkey,
+	fields: &SwapFields,
+	principal_in: bool,
+) -> Result<Instruction> {
+	let (pool_address, _) = get_pool_address(&fields.common.lysergic_tokenizer_address);
+	let data = client
+		.get_account_data(&pool_address)
+		.map_err(|err| anyhow!("Unable to fetch pool {}: {}", pool_address, err))?;
+	let pool = PoolState::unpack_initialized(&data)
+		.map_err(|err| anyhow!("Unable to decode pool {}: {}", pool_address, err))?;
+
+	let amount_in = fields.common.amount(client)?;
+	let (reserve_in, reserve_out) = if principal_in {
+		(pool.principal_reserve, pool.underlying_reserve)
+	} else {
+		(pool.underlying_reserve, pool.principal_reserve)
+	};
+	let quote = swap_out(amount_in, reserve_in, reserve_out, pool.fee_bps)
+		.ok_or_else(|| anyhow!("Unable to quote swap of {}", amount_in))?;
+	let min_amount_out = mul_div_floor(quote, 10_000 - fields.slippage_bps as u64, 10_000)
+		.ok_or_else(|| anyhow!("Unable to apply slippage to {}", quote))?;
+
+	println!("Pool Address: {}", pool_address);
+	println!("Quoted Output: {}", quote);
+	println!("Minimum Output: {}", min_amount_out);
+
+	instruction::swap(
+		&fields.common.lysergic_tokenizer_address,
+		&pool.principal_token_mint,
+		&pool.underlying_mint,
+		wallet_pubkey,
+		principal_in,
+		amount_in,
+		min_amount_out,
+	)
+	.map_err(|err| anyhow!("Unable to create `Swap` instruction: {}", err))
+}
+
+// Instructions creating the program config if `init_config`, then the underlying mint,
+// funding the wallet and initializing a 12 month tokenizer, in the order they must be sent
+#[cfg(feature = "dev")]
+fn dev_setup_instructions(
+	wallet_pubkey: &Pubkey,
+	mint_address: &Pubkey,
+	mint_rent: u64,
+	decimals: u8,
+	amount: u64,
+	timestamp: i64,
+	init_config: bool,
+) -> Result<Vec<Instruction>> {
+	let expiry = Expiry::TwelveMonths;
+	let expiry_date = expiry
+		.to_expiry_date(timestamp)
+		.ok_or_else(|| anyhow!("Unable to convert expiry to expiry date"))?;
+	let (lysergic_tokenizer_address, _) = derive_tokenizer(mint_address, expiry_date)?;
+	let underlying_vault_address = spl_associated_token_account::get_associated_token_address(
+		&lysergic_tokenizer_address,
+		mint_address,
+	);
+	let (principal_mint_address, _) = get_principal_mint_address(&lysergic_tokenizer_address);
+	let (yield_mint_address, _) = get_yield_mint_address(&lysergic_tokenizer_address);
+	let wallet_token_address =
+		spl_associated_token_account::get_associated_token_address(wallet_pubkey, mint_address);
+
+	println!("Underlying Mint Address: {}", mint_address);
+	println!("Wallet Token Address: {}", wallet_token_address);
+	println!("Tokenizer Address: {}", lysergic_tokenizer_address);
+	println!("Expiry Date: {}", expiry_label(expiry_date));
+	println!("Vault Address: {}", underlying_vault_address);
+	println!("Principal Mint Address: {}", principal_mint_address);
+	println!("Yield Mint Address: {}", yield_mint_address);
+
+	let mut instructions = Vec::new();
+
+	// The same tenors `config init` allows by default
+	if init_config {
+		instructions.push(instruction::init_config(wallet_pubkey, 12, 24)?);
+	}
+
+	instructions.extend([
+		solana_program::system_instruction::create_account(
+			wallet_pubkey,
+			mint_address,
+			mint_rent,
+			Mint::LEN as u64,
+			&spl_token::id(),
+		),
+		spl_token::instruction::initialize_mint2(
+			&spl_token::id(),
+			mint_address,
+			wallet_pubkey,
+			None,
+			decimals,
+		)?,
+		spl_associated_token_account::instruction::create_associated_token_account(
+			wallet_pubkey,
+			wallet_pubkey,
+			mint_address,
+			&spl_token::id(),
+		),
+		spl_token::instruction::mint_to(
+			&spl_token::id(),
+			mint_address,
+			&wallet_token_address,
+			wallet_pubkey,
+			&[],
+			amount,
+		)?,
+		instruction::init_tokenizer_and_mints(
+			&lysergic_tokenizer_address,
+			wallet_pubkey,
+			&underlying_vault_address,
+			mint_address,
+			&principal_mint_address,
+			&yield_mint_address,
+			expiry,
+			expiry_date,
+			0,
+		)?,
+	]);
+
+	Ok(instructions)
+}
+
+// Builds the transactions of a full terminate. With `split` the mints and the tokenizer are
+// terminated in separate transactions, mints first as the tokenizer is their authority, so
+// each gets a whole compute budget.
+fn terminate_transactions(
+	fields: &TerminateCommonFields,
+	authority: &Pubkey,
+	split: bool,
+) -> Result<Vec<Vec<Instruction>>> {
+	let (principal_mint_address, _) =
+		get_principal_mint_address(&fields.lysergic_tokenizer_address);
+	let (yield_mint_address, _) = get_yield_mint_address(&fields.lysergic_tokenizer_address);
+	let underlying_vault_address = spl_associated_token_account::get_associated_token_address(
+		&fields.lysergic_tokenizer_address,
+		&fields.underlying_mint_address,
+	);
+
+	if !split {
+		let terminate = instruction::terminate(
+			&fields.lysergic_tokenizer_address,
+			authority,
+			&underlying_vault_address,
+			&fields.underlying_mint_address,
+			&principal_mint_address,
+			&yield_mint_address,
+		)
+		.map_err(|err| anyhow!("Unable to create `Terminate` instruction: {}", err))?;
+
+		return Ok(vec![vec![terminate]]);
+	}
+
+	let terminate_mints = instruction::terminate_mints(
+		&fields.lysergic_tokenizer_address,
+		authority,
+		&principal_mint_address,
+		&yield_mint_address,
+	)
+	.map_err(|err| anyhow!("Unable to create `TerminateMints` instruction: {}", err))?;
+
+	let terminate_tokenizer = instruction::terminate_tokenizer(
+		&fields.lysergic_tokenizer_address,
+		authority,
+		&underlying_vault_address,
+		&fields.underlying_mint_address,
+	)
+	.map_err(|err| anyhow!("Unable to create `TerminateTokenizer` instruction: {}", err))?;
+
+	Ok(vec![vec![terminate_mints], vec![terminate_tokenizer]])
+}
+
+fn send_instructions(
+	client: &RpcClient,
+	wallet_keypair: &solana_sdk::signature::Keypair,
+	instructions: &[Instruction],
+) -> Result<()> {
+	let mut transaction = Transaction::new_with_payer(instructions, Some(&wallet_keypair.pubkey()));
+	let latest_blockhash = client
+		.get_latest_blockhash()
+		.map_err(|err| anyhow!("Unable to get latest blockhash: {}", err))?;
+
+	transaction.sign(&[wallet_keypair], latest_blockhash);
+	client
+		.send_and_confirm_transaction_with_spinner(&transaction)
+		.map_err(|err| anyhow!("Unable to send transaction: {}", err))?;
+
+	Ok(())
+}
+
+#[cfg(feature = "dev")]
+fn dev_setup(
+	client: &RpcClient,
+	wallet_keypair: &solana_sdk::signature::Keypair,
+	timestamp: i64,
+	fields: &DevSetupFields,
+) -> Result<()> {
+	let mint_keypair = solana_sdk::signature::Keypair::new();
+	let mint_rent = client.get_minimum_balance_for_rent_exemption(Mint::LEN)?;
+	let (config_address, _) = sclr_token::get_program_config_address();
+	let config = client
+		.get_account_with_commitment(&config_address, client.commitment())
+		.map_err(|err| anyhow!("Unable to fetch config {}: {}", config_address, err))?
+		.value;
+
+	let instructions = dev_setup_instructions(
+		&wallet_keypair.pubkey(),
+		&mint_keypair.pubkey(),
+		mint_rent,
+		fields.decimals,
+		fields.amount,
+		timestamp,
+		config.is_none(),
+	)?;
+
+	let mut transaction =
+		Transaction::new_with_payer(&instructions, Some(&wallet_keypair.pubkey()));
+	let latest_blockhash = client
+		.get_latest_blockhash()
+		.map_err(|err| anyhow!("Unable to get latest blockhash: {}", err))?;
+
+	transaction.sign(&[wallet_keypair, &mint_keypair], latest_blockhash);
+	client
+		.send_and_confirm_transaction_with_spinner(&transaction)
+		.map_err(|err| anyhow!("Unable to send transaction: {}", err))?;
+
+	Ok(())
 }
 
 fn main() -> Result<()> {
@@ -116,7 +737,18 @@ fn main() -> Result<()> {
 	);
 
 	let slot = client.get_slot()?;
-	let timestamp = client.get_block_time(slot)? as i64;
+	let timestamp = client.get_block_time(slot)?;
+
+	if let Some(tokenizer_address) = args.cmd.tokenizer_address() {
+		ensure_tokenizer_exists(&client, &tokenizer_address)?;
+	}
+
+	if matches!(
+		args.cmd,
+		Commands::Init(Initialize::Tokenizer(_) | Initialize::TokenizerMints(_))
+	) {
+		ensure_payer_can_fund_init(&client, &wallet_pubkey)?;
+	}
 
 	let instruction: Instruction = match args.cmd {
 		Commands::Init(init) => match init {
@@ -128,7 +760,7 @@ fn main() -> Result<()> {
 				let expiry_date = expiry.to_expiry_date(timestamp).expect("Invalid");
 
 				let (lysergic_tokenizer_address, _) =
-					get_tokenizer_address(&common_fields.underlying_mint_address, expiry_date);
+					derive_tokenizer(&common_fields.underlying_mint_address, expiry_date)?;
 
 				let underlying_vault_address =
 					spl_associated_token_account::get_associated_token_address(
@@ -146,6 +778,7 @@ fn main() -> Result<()> {
 				let fixed_apy = 0;
 
 				println!("Tokenizer Address: {}", lysergic_tokenizer_address);
+				println!("Expiry Date: {}", expiry_label(expiry_date));
 				println!("Vault Address: {}", underlying_vault_address);
 				println!("Principal Mint Address: {}", principal_mint_address);
 				println!("Yield Mint Address: {}", yield_mint_address);
@@ -158,6 +791,7 @@ fn main() -> Result<()> {
 					&principal_mint_address,
 					&yield_mint_address,
 					expiry,
+					expiry_date,
 					fixed_apy,
 				)
 				.map_err(|err| anyhow!("Unable to create init instruction: {}", err))?
@@ -172,7 +806,7 @@ fn main() -> Result<()> {
 					.expect("Unable to convert expiry to expiry date");
 
 				let (lysergic_tokenizer_address, _) =
-					get_tokenizer_address(&common_fields.underlying_mint_address, expiry_date);
+					derive_tokenizer(&common_fields.underlying_mint_address, expiry_date)?;
 
 				let (principal_mint_address, _) =
 					get_principal_mint_address(&lysergic_tokenizer_address);
@@ -189,6 +823,7 @@ fn main() -> Result<()> {
 					&principal_mint_address,
 					&yield_mint_address,
 					expiry,
+					expiry_date,
 				)
 				.map_err(|err| anyhow!("Unable to create `Initialize` instruction: {}", err))?
 			}
@@ -199,7 +834,7 @@ fn main() -> Result<()> {
 
 				let expiry_date = expiry.to_expiry_date(timestamp).expect("Invalid");
 				let (lysergic_tokenizer_address, _) =
-					get_tokenizer_address(&common_fields.underlying_mint_address, expiry_date);
+					derive_tokenizer(&common_fields.underlying_mint_address, expiry_date)?;
 
 				let underlying_vault_address =
 					spl_associated_token_account::get_associated_token_address(
@@ -217,6 +852,7 @@ fn main() -> Result<()> {
 				let fixed_apy = 0;
 
 				println!("Tokenizer Address: {}", lysergic_tokenizer_address);
+				println!("Expiry Date: {}", expiry_label(expiry_date));
 				println!("Vault Address: {}", underlying_vault_address);
 				println!("Principal Mint Address: {}", principal_mint_address);
 				println!("Yield Mint Address: {}", yield_mint_address);
@@ -228,7 +864,8 @@ fn main() -> Result<()> {
 					&common_fields.underlying_mint_address,
 					&principal_mint_address,
 					&yield_mint_address,
-					Expiry::from_i64(common_fields.expiry)?,
+					expiry,
+					expiry_date,
 					fixed_apy,
 				)
 				.map_err(|err| {
@@ -238,7 +875,32 @@ fn main() -> Result<()> {
 					)
 				})?
 			}
-			Initialize::Amm(_common_fields) => unimplemented!(),
+			Initialize::Amm(fields) => {
+				let expiry = Expiry::from_i64(fields.common.expiry).map_err(|err| {
+					anyhow!("Unable to parse the given value to `Expiry`: {}", err)
+				})?;
+
+				let expiry_date = expiry.to_expiry_date(timestamp).expect("Invalid");
+				let (lysergic_tokenizer_address, _) =
+					derive_tokenizer(&fields.common.underlying_mint_address, expiry_date)?;
+
+				let (principal_mint_address, _) =
+					get_principal_mint_address(&lysergic_tokenizer_address);
+
+				let (pool_address, _) = get_pool_address(&lysergic_tokenizer_address);
+
+				println!("Pool Address: {}", pool_address);
+				println!("LP Mint Address: {}", get_lp_mint_address(&pool_address).0);
+
+				instruction::initialize_amm(
+					&lysergic_tokenizer_address,
+					&wallet_pubkey,
+					&principal_mint_address,
+					&fields.common.underlying_mint_address,
+					fields.fee_bps,
+				)
+				.map_err(|err| anyhow!("Unable to create `InitializeAmm` instruction: {}", err))?
+			}
 		},
 		Commands::Tokenize(tokenize) => match tokenize {
 			Tokenize::Deposit(common_fields) => {
@@ -247,18 +909,37 @@ fn main() -> Result<()> {
 					&common_fields.underlying_mint_address,
 				);
 
+				let user_underlying_token_address =
+					spl_associated_token_account::get_associated_token_address(
+						&wallet_pubkey,
+						&common_fields.underlying_mint_address,
+					);
+
+				let (principal_mint_address, _) =
+					get_principal_mint_address(&common_fields.lysergic_tokenizer_address);
+				let (yield_mint_address, _) =
+					get_yield_mint_address(&common_fields.lysergic_tokenizer_address);
+
 				instruction::deposit_underlying(
 					&common_fields.lysergic_tokenizer_address,
-					&wallet_pubkey,
 					&underlying_vault,
-					&common_fields.underlying_mint_address,
-					common_fields.amount,
+					&principal_mint_address,
+					&yield_mint_address,
+					&wallet_pubkey,
+					&user_underlying_token_address,
+					common_fields.amount(&client)?,
 				)
 				.map_err(|err| anyhow!("Unable to create `Deposit` instruction: {}", err))?
 			}
 			Tokenize::Principal(common_fields) => {
+				let underlying_vault = spl_associated_token_account::get_associated_token_address(
+					&common_fields.lysergic_tokenizer_address,
+					&common_fields.underlying_mint_address,
+				);
 				let (principal_mint_address, _) =
 					get_principal_mint_address(&common_fields.lysergic_tokenizer_address);
+				let (yield_mint_address, _) =
+					get_yield_mint_address(&common_fields.lysergic_tokenizer_address);
 
 				let user_principal_token_address =
 					spl_associated_token_account::get_associated_token_address(
@@ -266,18 +947,34 @@ fn main() -> Result<()> {
 						&principal_mint_address,
 					);
 
-				instruction::tokenize_principal(
+				let instruction = instruction::tokenize_principal(
 					&common_fields.lysergic_tokenizer_address,
+					&underlying_vault,
 					&principal_mint_address,
+					&yield_mint_address,
 					&wallet_pubkey,
 					&user_principal_token_address,
-					common_fields.amount,
+					common_fields.amount(&client)?,
 				)
 				.map_err(|err| {
 					anyhow!("Unable to create `TokenizePrincipal` instruction: {}", err)
-				})?
+				})?;
+
+				send_instructions(&client, &wallet_keypair, &[instruction])?;
+				return print_token_balance(
+					&client,
+					"PT",
+					&principal_mint_address,
+					&user_principal_token_address,
+				);
 			}
 			Tokenize::Yield(common_fields) => {
+				let underlying_vault = spl_associated_token_account::get_associated_token_address(
+					&common_fields.lysergic_tokenizer_address,
+					&common_fields.underlying_mint_address,
+				);
+				let (principal_mint_address, _) =
+					get_principal_mint_address(&common_fields.lysergic_tokenizer_address);
 				let (yield_mint_address, _) =
 					get_yield_mint_address(&common_fields.lysergic_tokenizer_address);
 
@@ -287,14 +984,24 @@ fn main() -> Result<()> {
 						&yield_mint_address,
 					);
 
-				instruction::tokenize_yield(
+				let instruction = instruction::tokenize_yield(
 					&common_fields.lysergic_tokenizer_address,
+					&underlying_vault,
+					&principal_mint_address,
 					&yield_mint_address,
 					&wallet_pubkey,
 					&user_yield_token_address,
-					common_fields.amount,
+					common_fields.amount(&client)?,
 				)
-				.map_err(|err| anyhow!("Unable to create `TokenizeYield` instruction: {}", err))?
+				.map_err(|err| anyhow!("Unable to create `TokenizeYield` instruction: {}", err))?;
+
+				send_instructions(&client, &wallet_keypair, &[instruction])?;
+				return print_token_balance(
+					&client,
+					"YT",
+					&yield_mint_address,
+					&user_yield_token_address,
+				);
 			}
 			Tokenize::PrincipalYield(common_fields) => {
 				let underlying_vault = spl_associated_token_account::get_associated_token_address(
@@ -328,7 +1035,7 @@ fn main() -> Result<()> {
 						&yield_mint_address,
 					);
 
-				instruction::deposit_and_tokenize(
+				let instruction = instruction::deposit_and_tokenize(
 					&common_fields.lysergic_tokenizer_address,
 					&underlying_vault,
 					&principal_mint_address,
@@ -337,28 +1044,44 @@ fn main() -> Result<()> {
 					&user_underlying_token_address,
 					&user_principal_token_address,
 					&user_yield_token_address,
-					common_fields.amount,
+					common_fields.amount(&client)?,
 				)
 				.map_err(|err| {
 					anyhow!("Unable to create `DepositAndTokenize` instruction: {}", err)
-				})?
+				})?;
+
+				send_instructions(&client, &wallet_keypair, &[instruction])?;
+				print_token_balance(
+					&client,
+					"PT",
+					&principal_mint_address,
+					&user_principal_token_address,
+				)?;
+				return print_token_balance(
+					&client,
+					"YT",
+					&yield_mint_address,
+					&user_yield_token_address,
+				);
 			}
 		},
 		Commands::Redeem(redeem) => match redeem {
-			Redeem::Principal(common_fields) => {
+			Redeem::Principal(fields) => {
 				let underlying_vault_address =
 					spl_associated_token_account::get_associated_token_address(
-						&common_fields.lysergic_tokenizer_address,
-						&common_fields.underlying_mint_address,
+						&fields.common.lysergic_tokenizer_address,
+						&fields.common.underlying_mint_address,
 					);
 
 				let (principal_mint_address, _) =
-					get_principal_mint_address(&common_fields.lysergic_tokenizer_address);
+					get_principal_mint_address(&fields.common.lysergic_tokenizer_address);
+				let (yield_mint_address, _) =
+					get_yield_mint_address(&fields.common.lysergic_tokenizer_address);
 
 				let user_underlying_token_address =
 					spl_associated_token_account::get_associated_token_address(
 						&wallet_pubkey,
-						&common_fields.underlying_mint_address,
+						&fields.common.underlying_mint_address,
 					);
 
 				let user_principal_token_address =
@@ -368,14 +1091,17 @@ fn main() -> Result<()> {
 					);
 
 				instruction::redeem_mature_principal(
-					&common_fields.lysergic_tokenizer_address,
+					&fields.common.lysergic_tokenizer_address,
 					&underlying_vault_address,
-					&common_fields.underlying_mint_address,
+					&fields.common.underlying_mint_address,
 					&principal_mint_address,
+					&yield_mint_address,
 					&wallet_pubkey,
 					&user_underlying_token_address,
 					&user_principal_token_address,
-					common_fields.amount,
+					fields.common.amount(&client)?,
+					fields.common.unwrap,
+					fields.close_principal_ata,
 				)
 				.map_err(|err| {
 					anyhow!(
@@ -385,6 +1111,15 @@ fn main() -> Result<()> {
 				})?
 			}
 			Redeem::Yield(common_fields) => {
+				let underlying_vault_address =
+					spl_associated_token_account::get_associated_token_address(
+						&common_fields.lysergic_tokenizer_address,
+						&common_fields.underlying_mint_address,
+					);
+
+				let (principal_mint_address, _) =
+					get_principal_mint_address(&common_fields.lysergic_tokenizer_address);
+
 				let (yield_mint_address, _) =
 					get_yield_mint_address(&common_fields.lysergic_tokenizer_address);
 
@@ -402,12 +1137,16 @@ fn main() -> Result<()> {
 
 				instruction::claim_yield(
 					&common_fields.lysergic_tokenizer_address,
+					&underlying_vault_address,
 					&common_fields.underlying_mint_address,
+					&principal_mint_address,
 					&yield_mint_address,
 					&wallet_pubkey,
 					&user_underlying_token_address,
 					&user_yield_token_address,
-					common_fields.amount,
+					common_fields.amount(&client)?,
+					false,
+					common_fields.unwrap,
 				)
 				.map_err(|err| anyhow!("Unable to create `ClaimYield` instruction: {}", err))?
 			}
@@ -452,7 +1191,10 @@ fn main() -> Result<()> {
 					&user_underlying_token_address,
 					&user_principal_token_address,
 					&user_yield_token_address,
-					common_fields.amount,
+					common_fields.amount(&client)?,
+					0,
+					common_fields.unwrap,
+					instruction::RedeemOrder::PrincipalFirst,
 				)
 				.map_err(|err| {
 					anyhow!(
@@ -463,39 +1205,32 @@ fn main() -> Result<()> {
 			}
 		},
 		Commands::Terminate(terminate) => match terminate {
-			Terminate::Terminate(common_fields) => {
-				let (principal_mint_address, _) =
-					get_principal_mint_address(&common_fields.lysergic_tokenizer_address);
-				let (yield_mint_address, _) =
-					get_yield_mint_address(&common_fields.lysergic_tokenizer_address);
-				instruction::terminate(
+			Terminate::Terminate(fields) => {
+				fields.common.confirm()?;
+				for instructions in
+					terminate_transactions(&fields.common, &wallet_pubkey, fields.split)?
+				{
+					send_instructions(&client, &wallet_keypair, &instructions)?;
+				}
+				return Ok(());
+			}
+			Terminate::TerminateTokenizer(common_fields) => {
+				common_fields.confirm()?;
+				instruction::terminate_tokenizer(
 					&common_fields.lysergic_tokenizer_address,
 					&wallet_pubkey,
 					&spl_associated_token_account::get_associated_token_address(
 						&common_fields.lysergic_tokenizer_address,
 						&common_fields.underlying_mint_address,
 					),
-					&spl_associated_token_account::get_associated_token_address(
-						&common_fields.lysergic_tokenizer_address,
-						&principal_mint_address,
-					),
-					&spl_associated_token_account::get_associated_token_address(
-						&common_fields.lysergic_tokenizer_address,
-						&yield_mint_address,
-					),
+					&common_fields.underlying_mint_address,
 				)
-				.map_err(|err| anyhow!("Unable to create `Terminate` instruction: {}", err))?
+				.map_err(|err| {
+					anyhow!("Unable to create `TerminateTokenizer` instruction: {}", err)
+				})?
 			}
-			Terminate::TerminateTokenizer(common_fields) => instruction::terminate_tokenizer(
-				&common_fields.lysergic_tokenizer_address,
-				&wallet_pubkey,
-				&spl_associated_token_account::get_associated_token_address(
-					&common_fields.lysergic_tokenizer_address,
-					&common_fields.underlying_mint_address,
-				),
-			)
-			.map_err(|err| anyhow!("Unable to create `TerminateTokenizer` instruction: {}", err))?,
 			Terminate::TerminateMints(common_fields) => {
+				common_fields.confirm()?;
 				let (principal_mint_address, _) =
 					get_principal_mint_address(&common_fields.lysergic_tokenizer_address);
 				let (yield_mint_address, _) =
@@ -509,22 +1244,79 @@ fn main() -> Result<()> {
 				.map_err(|err| anyhow!("Unable to create `TerminateMints` instruction: {}", err))?
 			}
 		},
+		Commands::Config(config) => match config {
+			Config::Init(fields) => {
+				instruction::init_config(&wallet_pubkey, fields.min_tenor, fields.max_tenor)
+					.map_err(|err| anyhow!("Unable to create `InitConfig` instruction: {}", err))?
+			}
+			Config::Update(fields) => {
+				instruction::update_config(&wallet_pubkey, fields.min_tenor, fields.max_tenor)
+					.map_err(|err| {
+						anyhow!("Unable to create `UpdateConfig` instruction: {}", err)
+					})?
+			}
+		},
 		Commands::Swap(swap) => match swap {
-			Swap::Principal(_common_fields) => unimplemented!(),
-			Swap::Yield(_common_fields) => unimplemented!(),
-			Swap::Underlying(_common_fields) => unimplemented!(),
+			Swap::Principal(fields) => swap_instruction(&client, &wallet_pubkey, &fields, true)?,
+			Swap::Yield(_) => {
+				return Err(anyhow!(
+					"Yield tokens cannot be swapped, the pool only trades principal against underlying"
+				))
+			}
+			Swap::Underlying(fields) => swap_instruction(&client, &wallet_pubkey, &fields, false)?,
 		},
+		Commands::Info(info_fields) => {
+			return print_tokenizer_info(&client, &info_fields.lysergic_tokenizer_address)
+		}
+		Commands::List => {
+			for (address, state) in all_tokenizers(&client)? {
+				println!(
+					"{} {} {}",
+					address,
+					state.underlying_mint,
+					expiry_label(state.expiry_date)
+				);
+			}
+			return Ok(());
+		}
+		#[cfg(feature = "dev")]
+		Commands::Dev(Dev::Setup(fields)) => {
+			return dev_setup(&client, &wallet_keypair, timestamp, &fields)
+		}
 	};
 
-	let mut transaction = Transaction::new_with_payer(&[instruction], Some(&wallet_pubkey));
-	let latest_blockchash = client
-		.get_latest_blockhash()
-		.map_err(|err| anyhow!("Unable to get latest blockhash: {}", err))?;
-
-	transaction.sign(&[&wallet_keypair], latest_blockchash);
-	client
-		.send_and_confirm_transaction_with_spinner(&transaction)
-		.map_err(|err| anyhow!("Unable to send transaction: {}", err))?;
+	send_instructions(&client, &wallet_keypair, &[instruction])
+}
 
-	Ok(())
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ui_amount_converts_to_base_units() {
+		assert_eq!(ui_amount_to_base_units("1.5", 6).unwrap(), 1_500_000);
+		assert_eq!(ui_amount_to_base_units("1", 6).unwrap(), 1_000_000);
+		assert_eq!(ui_amount_to_base_units("0.000001", 6).unwrap(), 1);
+		assert_eq!(ui_amount_to_base_units("42", 0).unwrap(), 42);
+	}
+
+	#[test]
+	fn ui_amount_rejects_invalid_input() {
+		assert!(ui_amount_to_base_units("0.0000001", 6).is_err());
+		assert!(ui_amount_to_base_units("1.5", 0).is_err());
+		assert!(ui_amount_to_base_units("-1", 6).is_err());
+		assert!(ui_amount_to_base_units("1e6", 6).is_err());
+		assert!(ui_amount_to_base_units("18446744073709.551616", 6).is_err());
+	}
+
+	#[test]
+	fn base_units_round_trip() {
+		for (amount, decimals) in [(1_500_000, 6), (1, 6), (0, 9), (42, 0)] {
+			let ui_amount = base_units_to_ui_amount(amount, decimals);
+			assert_eq!(
+				ui_amount_to_base_units(&ui_amount, decimals).unwrap(),
+				amount
+			);
+		}
+	}
 }
@@ -9,10 +9,15 @@ use {
 	thiserror::Error,
 };
 
+// Codes start at 6000 so they cannot be confused with the low `Custom` codes used by SPL
+// programs, e.g. `TokenError`, when those are returned through a CPI. New variants must be
+// appended so existing codes never change; clients should decode through `from_code` or
+// `from_program_error` and treat `None` as an error from a newer program version.
 #[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+#[non_exhaustive]
 pub enum TokenizerError {
 	#[error("Invalid Instruction")]
-	InvalidInstruction,
+	InvalidInstruction = 6000,
 	#[error("Tokenizer Already Initialized")]
 	TokenizerAlreadyInitialized,
 	#[error("Tokenizer Not Initialized")]
@@ -41,6 +46,30 @@ pub enum TokenizerError {
 	InsufficientFunds,
 	#[error("Vault Is Not Empty")]
 	VaultNotEmpty,
+	#[error("Mints Have Not Been Terminated")]
+	MintsNotTerminated,
+	#[error("Tokenizer Has Deposits")]
+	TokenizerHasDeposits,
+	#[error("Slippage Exceeded")]
+	SlippageExceeded,
+	#[error("Mint Supply Is Not Zero")]
+	MintSupplyNotZero,
+	#[error("Program Config Not Initialized")]
+	ConfigNotInitialized,
+	#[error("Tenor Not Enabled")]
+	TenorNotEnabled,
+	#[error("Cooldown Active")]
+	CooldownActive,
+	#[error("Vault Is Insolvent")]
+	VaultInsolvent,
+	#[error("Token Account Is Not Empty")]
+	TokenAccountNotEmpty,
+	#[error("Pool Not Initialized")]
+	PoolNotInitialized,
+	#[error("Incorrect Pool Address")]
+	IncorrectPoolAddress,
+	#[error("Program Frozen")]
+	ProgramFrozen,
 }
 
 impl From<TokenizerError> for ProgramError {
@@ -49,6 +78,22 @@ impl From<TokenizerError> for ProgramError {
 	}
 }
 
+impl TokenizerError {
+	// Maps a `ProgramError::Custom` code back to the tokenizer error, if it is one
+	pub fn from_code(code: u32) -> Option<Self> {
+		Self::from_u32(code)
+	}
+
+	// Decodes a program error returned by the tokenizer, yielding `None` for non-custom errors
+	// and for codes this client does not know about
+	pub fn from_program_error(error: &ProgramError) -> Option<Self> {
+		match error {
+			ProgramError::Custom(code) => Self::from_code(*code),
+			_ => None,
+		}
+	}
+}
+
 impl<T> DecodeError<T> for TokenizerError {
 	fn type_of() -> &'static str {
 		"Lysergic tokenizer error"
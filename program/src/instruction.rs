@@ -1,7 +1,12 @@
 use {
-	crate::Expiry,
+	crate::{
+		derive_tokenizer, get_lp_mint_address, get_pool_address, get_principal_mint_address,
+		get_program_config_address, get_user_position_address, get_yield_mint_address,
+		pool::MAX_POOL_FEE_BPS, Expiry, BPS_DENOMINATOR,
+	},
 	borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
 	solana_program::{
+		bpf_loader_upgradeable,
 		instruction::{AccountMeta, Instruction},
 		program_error::ProgramError,
 		pubkey::Pubkey,
@@ -10,6 +15,14 @@ use {
 	spl_token,
 };
 
+/// Which leg of a `RedeemPrincipalAndYield` is settled first. Only the order of the transfers
+/// and logs differs, the amounts paid out are the same either way.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Copy, Debug, PartialEq)]
+pub enum RedeemOrder {
+	PrincipalFirst,
+	YieldFirst,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
 pub enum TokenizerInstruction {
 	/// Initializes the Tokenizer
@@ -22,6 +35,8 @@ pub enum TokenizerInstruction {
 	/// 3. `[]` Underlying mint account
 	/// 4. `[]` Token program
 	/// 5. `[]` System program
+	/// 6. `[]` Associated Token Program
+	/// 7. `[]` Program config account
 	InitializeTokenizer {
 		/// The public key of the underlying mint
 		underlying_mint: Pubkey,
@@ -31,24 +46,32 @@ pub enum TokenizerInstruction {
 		yield_token_mint: Pubkey,
 		/// The expiry of the LysergicTokenizer
 		expiry: Expiry,
-		/// The fixed APY of the Principal token
+		/// The expiry date resolved from `expiry`, used to derive the tokenizer address
+		expiry_date: i64,
+		/// The fixed APY of the Principal token in basis points
 		fixed_apy: u64,
 	},
 
 	/// Initializes the principal and yield token mints
+	/// The tokenizer must already be initialized, and deposits are only accepted afterwards
+	/// Only `expiry_date` is checked, against the tokenizer's, so this may land on a later day
 	///
 	/// Accounts expected:
 	///
 	/// 0. `[writable]` Tokenizer account
 	/// 1. `[writable, signer]` Authority
-	/// 2. `[writable]` Principal token mint account
-	/// 3. `[writable]` Yield token mint account
-	/// 4. `[]` Token program
+	/// 2. `[]` Underlying mint account
+	/// 3. `[writable]` Principal token mint account
+	/// 4. `[writable]` Yield token mint account
+	/// 5. `[]` Token program
+	/// 6. `[]` System program
 	InitializeMints {
 		/// The public key of the underlying mint
 		underlying_mint: Pubkey,
-		/// The expiry of the LysergicTokenizer
+		/// The expiry of the LysergicTokenizer, only checked by `InitializeTokenizer`
 		expiry: Expiry,
+		/// The expiry date resolved from `expiry`, used to derive the tokenizer address
+		expiry_date: i64,
 	},
 
 	/// Helper function to initialize the LysergicTokenizer and the mints
@@ -58,11 +81,13 @@ pub enum TokenizerInstruction {
 	/// 0. `[writable]` Tokenizer account
 	/// 1. `[writable,signer]` Authority
 	/// 2. `[writable]` Underlying vault account
-	/// 3. `[writable]` Principal token mint account
-	/// 4. `[writable]` Yield token mint account
-	/// 5. `[]` Token program
-	/// 6. `[]` Associated Token Program
+	/// 3. `[]` Underlying mint account
+	/// 4. `[writable]` Principal token mint account
+	/// 5. `[writable]` Yield token mint account
+	/// 6. `[]` Token program
 	/// 7. `[]` System program
+	/// 8. `[]` Associated Token Program
+	/// 9. `[]` Program config account
 	InitializeTokenizerAndMints {
 		/// The public key of the underlying mint
 		underlying_mint: Pubkey,
@@ -72,11 +97,14 @@ pub enum TokenizerInstruction {
 		yield_token_mint: Pubkey,
 		/// The expiry of the LysergicTokenizer
 		expiry: Expiry,
-		/// The fixed APY of the Principal token
+		/// The expiry date resolved from `expiry`, used to derive the tokenizer address
+		expiry_date: i64,
+		/// The fixed APY of the Principal token in basis points
 		fixed_apy: u64,
 	},
 
-	/// Deposits the underlying token into the LysergicTokenizer
+	/// Deposits the underlying token into the LysergicTokenizer before expiry, crediting it to
+	/// the user's position so it can be tokenized with `TokenizePrincipal` and `TokenizeYield`
 	///
 	/// Accounts expected:
 	///
@@ -85,12 +113,18 @@ pub enum TokenizerInstruction {
 	/// 2. `[writable, signer]` User account
 	/// 3. `[writable]` User underlying token account
 	/// 4. `[]` Token program
+	/// 5. `[]` System program
+	/// 6. `[writable]` User position account
+	/// 7. `[]` Principal token mint account
+	/// 8. `[]` Yield token mint account
 	DepositUnderlying {
 		/// The amount of the underlying token to deposit
 		amount: u64,
 	},
 
-	/// Tokenizes the underlying token into principal tokens
+	/// Tokenizes underlying the user deposited with `DepositUnderlying` into principal tokens
+	/// Fails with `InsufficientFunds` beyond the user's deposits not yet tokenized into
+	/// principal
 	///
 	/// Accounts expected:
 	///
@@ -100,11 +134,16 @@ pub enum TokenizerInstruction {
 	/// 3. `[writable]` User principal token account
 	/// 4. `[]` Token program
 	/// 5. `[]` System program
+	/// 6. `[]` Associated Token Program
+	/// 7. `[writable]` User position account
+	/// 8. `[]` Underlying vault account
+	/// 9. `[]` Yield token mint account
 	TokenizePrincipal {
 		/// The amount of the underlying token to tokenize
 		amount: u64,
 	},
-	/// Tokenizes the underlying token into yield tokens
+	/// Tokenizes underlying the user deposited with `DepositUnderlying` into yield tokens
+	/// Fails with `InsufficientFunds` beyond the user's deposits not yet tokenized into yield
 	///
 	/// Accounts expected:
 	///
@@ -114,6 +153,10 @@ pub enum TokenizerInstruction {
 	/// 3. `[writable]` User yield token account
 	/// 4. `[]` Token program
 	/// 5. `[]` System program
+	/// 6. `[]` Associated Token Program
+	/// 7. `[writable]` User position account
+	/// 8. `[]` Underlying vault account
+	/// 9. `[]` Principal token mint account
 	TokenizeYield {
 		/// The amount of the underlying token to tokenize
 		amount: u64,
@@ -133,12 +176,15 @@ pub enum TokenizerInstruction {
 	/// 7. `[writable]` User yield token account
 	/// 8. `[]` Token program
 	/// 9. `[]` System program
+	/// 10. `[]` Associated Token Program
+	/// 11. `[writable]` User position account
 	DepositAndTokenize {
 		/// The amount of the underlying token to deposit
 		amount: u64,
 	},
 
-	/// Redeems the principal and yield tokens for the underlying token
+	/// Redeems the principal and yield tokens for the underlying token. The full `amount` of
+	/// both is burned, even if the yield leg's payout is capped.
 	///
 	/// Accounts expected:
 	///
@@ -153,7 +199,19 @@ pub enum TokenizerInstruction {
 	/// 8. `[writable]` User yield token account
 	/// 9. `[]` Token program
 	/// 10. `[]` System program
-	RedeemPrincipalAndYield { amount: u64 },
+	/// 11. `[]` Associated Token Program
+	/// 12. `[writable]` User position account
+	RedeemPrincipalAndYield {
+		/// The amount of the principal and yield tokens to redeem
+		amount: u64,
+		/// Abort with `SlippageExceeded` if the principal and yield payouts sum to less
+		min_total_underlying_out: u64,
+		/// Close the user's underlying token account afterwards to deliver native SOL, if the
+		/// underlying is the native mint
+		unwrap: bool,
+		/// Whether the principal or the yield is settled first
+		redeem_order: RedeemOrder,
+	},
 
 	/// Redeems the principal token for the underlying token
 	///
@@ -165,32 +223,56 @@ pub enum TokenizerInstruction {
 	/// 3. `[writable]` Principal token mint account
 	/// 4. `[writable, signer]` User account
 	/// 5. `[writable]` User underlying token account
-	/// 7. `[writable]` User principal token account
-	/// 8. `[]` Token program
-	/// 9. `[]` System program
+	/// 6. `[writable]` User principal token account
+	/// 7. `[]` Token program
+	/// 8. `[]` System program
+	/// 9. `[]` Associated Token Program
+	/// 10. `[]` Yield token mint account
 	RedeemMaturePrincipal {
 		/// The amount of the principal token to redeem
 		principal_amount: u64,
+		/// Close the user's underlying token account afterwards to deliver native SOL, if the
+		/// underlying is the native mint
+		unwrap: bool,
+		/// Close the user's principal token account afterwards, returning its rent to the
+		/// user; fails with `TokenAccountNotEmpty` unless the full balance was redeemed
+		close_principal_ata: bool,
 	},
 
 	/// Claims the yield
 	///
+	/// The claim is capped so the vault always holds at least the principal supply and the
+	/// amount actually claimed is written to return data as a little-endian `u64`. Every
+	/// settled claim, including those made by `RedeemPrincipalAndYield` and `BatchRedeem`,
+	/// logs `ClaimYield <tokenizer> <user> <amount>`.
+	///
 	/// Accounts expected:
 	///
 	/// 0. `[writable]` Tokenizer account
-	/// 1. `[writable]` Yield token mint account
-	/// 2. `[writable]` Underlying vault account
-	/// 3. `[writable, signer]` User account
-	/// 4. `[writable]` User underlying token account
-	/// 5. `[writable]` User yield token account
-	/// 6. `[]` Token program
-	/// 7. `[]` System program
+	/// 1. `[writable]` Underlying vault account
+	/// 2. `[]` Underlying mint account
+	/// 3. `[]` Principal token mint account
+	/// 4. `[writable]` Yield token mint account
+	/// 5. `[writable, signer]` User account
+	/// 6. `[writable]` User underlying token account
+	/// 7. `[writable]` User yield token account
+	/// 8. `[]` Token program
+	/// 9. `[]` System program
+	/// 10. `[]` Associated Token Program
+	/// 11. `[writable]` User position account
 	ClaimYield {
 		/// The amount of the underlying token to claim
 		yield_amount: u64,
+		/// Fail with `InsufficientFunds` instead of capping the claim
+		strict: bool,
+		/// Close the user's underlying token account afterwards to deliver native SOL, if the
+		/// underlying is the native mint
+		unwrap: bool,
 	},
 
 	/// Terminates the LysergicTokenizer and Mints
+	/// Components that are already terminated, e.g. mints terminated by `TerminateMints`, are
+	/// skipped
 	///
 	/// Accounts expected:
 	///
@@ -201,11 +283,17 @@ pub enum TokenizerInstruction {
 	/// 4. `[writable]` Yield token mint account
 	/// 5. `[]` Token program
 	/// 6. `[]` System program
+	/// 7. `[]` Underlying mint account
+	/// 8. `[writable]` Authority underlying token account
+	/// 9. `[]` Associated Token Program
 	Terminate,
 
-	/// Terminates the Tokenizer 
-	/// Should only be called AFTER the mints have been terminated
+	/// Terminates the Tokenizer
+	/// Fails with `MintsNotTerminated` unless the mints have been terminated first
 	/// as the tokenizer is the owner of the mints
+	/// Any underlying left in the vault is transferred to the authority's associated
+	/// token account, created if needed, before the vault is closed. The mints can only be
+	/// terminated once every token is redeemed, so this is only ever dust.
 	///
 	/// Accounts expected:
 	///
@@ -214,11 +302,18 @@ pub enum TokenizerInstruction {
 	/// 2. `[writable]` Underlying vault account
 	/// 3. `[]` Token Program
 	/// 4. `[]` System Program
+	/// 5. `[]` Underlying mint account
+	/// 6. `[writable]` Authority underlying token account
+	/// 7. `[]` Associated Token Program
 	TerminateTokenizer,
 
 	/// Terminates the Mints associated with the LysergicTokenizer
 	/// This function cannot be called unless the LysergicTokenizer HAS NOT
 	/// been terminated as the tokenizer itself is the owner of the mints
+	/// SPL Token cannot close mints, so their mint authority is revoked instead and the mint
+	/// accounts, with their rent, are left in place
+	/// Fails with `MintSupplyNotZero` while any principal or yield tokens are outstanding;
+	/// yield tokens with nothing left to claim can be burned by their holders
 	///
 	/// Accounts expected:
 	///
@@ -229,9 +324,267 @@ pub enum TokenizerInstruction {
 	/// 4. `[]` Token program
 	/// 5. `[]` System program
 	TerminateMints,
+
+	/// Writes the current exchange rate of the principal token, in underlying per
+	/// principal scaled by `RATE_SCALE`, to return data as a little-endian `u64`
+	///
+	/// Takes no writable accounts or signers, so it can be called through
+	/// `simulateTransaction` without a funded fee payer signing anything.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Tokenizer account
+	GetExchangeRate,
+
+	/// Recreates the underlying vault if it has been closed while the tokenizer is live
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Tokenizer account
+	/// 1. `[writable, signer]` Authority
+	/// 2. `[writable]` Underlying vault account
+	/// 3. `[]` Underlying mint account
+	/// 4. `[]` Token program
+	/// 5. `[]` System program
+	/// 6. `[]` Associated Token Program
+	RecreateVault,
+
+	/// Updates the fixed APY of the principal token, only allowed while no principal or yield
+	/// tokens are outstanding
+	/// Like at initialization, an APY discounting principal beyond
+	/// `MAX_PRINCIPAL_PER_UNDERLYING` over the remaining tenor fails with `InvalidArgument`
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Tokenizer account
+	/// 1. `[signer]` Authority
+	/// 2. `[]` Principal token mint account
+	/// 3. `[]` Yield token mint account
+	UpdateFixedApy {
+		/// The new fixed APY of the Principal token in basis points
+		fixed_apy: u64,
+	},
+
+	/// Redeems mature principal and claims yield for several users at once
+	///
+	/// Each entry is settled as a `RedeemMaturePrincipal` followed by a non-strict `ClaimYield`,
+	/// skipping whichever amount is zero, so any principal requires the tokenizer to have expired.
+	/// At most `MAX_BATCH_REDEEM_ENTRIES` entries are accepted.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Tokenizer account
+	/// 1. `[writable]` Underlying vault account
+	/// 2. `[]` Underlying mint account
+	/// 3. `[writable]` Principal token mint account
+	/// 4. `[writable]` Yield token mint account
+	/// 5. `[]` Token program
+	/// 6. `[]` System program
+	/// 7. `[]` Associated Token Program
+	///
+	/// Followed by one group per entry:
+	///
+	/// 0. `[writable, signer]` User account
+	/// 1. `[writable]` User underlying token account
+	/// 2. `[writable]` User principal token account
+	/// 3. `[writable]` User yield token account
+	/// 4. `[writable]` User position account
+	BatchRedeem {
+		/// The principal and yield amounts to redeem, one pair per account group
+		entries: Vec<(u64, u64)>,
+	},
+
+	/// Terminates only the principal token mint once its supply is zero, leaving the yield
+	/// mint and vault open so yield can still be claimed. Like `TerminateMints` this revokes
+	/// the mint authority rather than closing the mint.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Tokenizer account
+	/// 1. `[writable, signer]` Authority
+	/// 2. `[writable]` Principal token mint account
+	/// 3. `[]` Token program
+	TerminatePrincipalMint,
+
+	/// Creates the program config, setting the signer as its admin
+	/// Only the program's upgrade authority may create it
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Program config account
+	/// 1. `[writable, signer]` Admin, the program's upgrade authority
+	/// 2. `[]` Program data account
+	/// 3. `[]` System program
+	InitConfig {
+		/// The shortest tenor, in months, new tokenizers may be created with
+		min_tenor: i64,
+		/// The longest tenor, in months, new tokenizers may be created with
+		max_tenor: i64,
+	},
+
+	/// Updates the range of tenors new tokenizers may be created with
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Program config account
+	/// 1. `[signer]` Admin
+	UpdateConfig {
+		/// The shortest tenor, in months, new tokenizers may be created with
+		min_tenor: i64,
+		/// The longest tenor, in months, new tokenizers may be created with
+		max_tenor: i64,
+	},
+
+	/// Updates how long users must hold after tokenizing before they can claim yield, which
+	/// also covers `RedeemPrincipalAndYield` and `BatchRedeem` as they claim through it
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Tokenizer account
+	/// 1. `[signer]` Authority
+	UpdateMinHold {
+		/// Seconds since the user's last tokenization, zero disables the cooldown
+		min_hold_seconds: i64,
+	},
+
+	/// Checks the tokenizer's books and writes the result to return data as a `u8` healthy
+	/// flag followed by the vault balance, principal supply and yield supply, each a
+	/// little-endian `u64`
+	///
+	/// Healthy means the vault covers the outstanding principal 1:1, principal and yield are
+	/// outstanding in equal amounts until maturity, and no more yield has been minted than
+	/// underlying deposited. Yield claimed or burned before maturity breaks the equality. Like
+	/// `GetExchangeRate` it is read-only, so it can be called through `simulateTransaction`.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[]` Tokenizer account
+	/// 1. `[]` Underlying vault account
+	/// 2. `[]` Principal token mint account
+	/// 3. `[]` Yield token mint account
+	Reconcile,
+
+	/// Marks the underlying as rebasing, so principal is redeemed for
+	/// `principal_amount / principal_supply * min(vault_balance, principal_supply)` rather than
+	/// 1:1. Rebase losses are shared by the principal holders, while rebase gains are surplus
+	/// claimable by the yield holders. Only allowed while no principal or yield tokens are
+	/// outstanding.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Tokenizer account
+	/// 1. `[signer]` Authority
+	/// 2. `[]` Principal token mint account
+	/// 3. `[]` Yield token mint account
+	UpdateRebasing {
+		/// Whether the underlying rebases
+		rebasing: bool,
+	},
+
+	/// Unwinds a fraction of the user's matched principal and yield, i.e. the smaller of the
+	/// two balances, before maturity. Settled as a `RedeemPrincipalAndYield` with the yield leg
+	/// first, except that the principal is paid out at the current exchange rate, see
+	/// `GetExchangeRate`, rather than 1:1. The discount stays in the vault as surplus.
+	///
+	/// Accounts expected are the same as `RedeemPrincipalAndYield`.
+	Unwind {
+		/// The fraction of the matched position to unwind, in basis points
+		fraction_bps: u16,
+		/// Abort with `SlippageExceeded` if the principal and yield payouts sum to less
+		min_total_underlying_out: u64,
+	},
+
+	/// Creates a constant-product AMM pool between the tokenizer's principal token and its
+	/// underlying, along with the pool's LP token mint and token accounts
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Pool account
+	/// 1. `[]` Tokenizer account
+	/// 2. `[writable, signer]` Authority
+	/// 3. `[writable]` LP token mint account
+	/// 4. `[writable]` Pool principal token account
+	/// 5. `[writable]` Pool underlying token account
+	/// 6. `[]` Principal token mint account
+	/// 7. `[]` Underlying mint account
+	/// 8. `[]` Token program
+	/// 9. `[]` System program
+	/// 10. `[]` Associated token program
+	InitializeAmm {
+		/// The fee charged on the input of a swap, in basis points
+		fee_bps: u16,
+	},
+
+	/// Deposits principal and underlying into the pool in exchange for LP tokens. Only the
+	/// amounts matching the pool's current ratio are taken.
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Pool account
+	/// 1. `[writable]` Pool principal token account
+	/// 2. `[writable]` Pool underlying token account
+	/// 3. `[writable]` LP token mint account
+	/// 4. `[writable, signer]` User account
+	/// 5. `[writable]` User principal token account
+	/// 6. `[writable]` User underlying token account
+	/// 7. `[writable]` User LP token account
+	/// 8. `[]` Token program
+	/// 9. `[]` System program
+	/// 10. `[]` Associated token program
+	AddLiquidity {
+		/// The most principal to deposit
+		principal_amount: u64,
+		/// The most underlying to deposit
+		underlying_amount: u64,
+		/// The fewest LP tokens to accept
+		min_lp_out: u64,
+	},
+
+	/// Burns LP tokens for their share of the pool's principal and underlying
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Pool account
+	/// 1. `[writable]` Pool principal token account
+	/// 2. `[writable]` Pool underlying token account
+	/// 3. `[writable]` LP token mint account
+	/// 4. `[signer]` User account
+	/// 5. `[writable]` User principal token account
+	/// 6. `[writable]` User underlying token account
+	/// 7. `[writable]` User LP token account
+	/// 8. `[]` Token program
+	RemoveLiquidity {
+		/// The amount of LP tokens to burn
+		lp_amount: u64,
+		/// The least principal to accept
+		min_principal_out: u64,
+		/// The least underlying to accept
+		min_underlying_out: u64,
+	},
+
+	/// Swaps principal for underlying, or underlying for principal, against the pool
+	///
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` Pool account
+	/// 1. `[writable]` Pool principal token account
+	/// 2. `[writable]` Pool underlying token account
+	/// 3. `[signer]` User account
+	/// 4. `[writable]` User principal token account
+	/// 5. `[writable]` User underlying token account
+	/// 6. `[]` Token program
+	Swap {
+		/// Whether principal is swapped in for underlying, rather than the reverse
+		principal_in: bool,
+		/// The amount swapped in
+		amount_in: u64,
+		/// The least to accept out
+		min_amount_out: u64,
+	},
 }
 
 /// Creates an `InitializeTokenizer` instruction
+#[allow(clippy::too_many_arguments)]
 pub fn init_tokenizer(
 	tokenizer: &Pubkey,
 	authority: &Pubkey,
@@ -240,6 +593,7 @@ pub fn init_tokenizer(
 	principal_token_mint: &Pubkey,
 	yield_token_mint: &Pubkey,
 	expiry: Expiry,
+	expiry_date: i64,
 	fixed_apy: u64,
 ) -> Result<Instruction, ProgramError> {
 	Ok(Instruction::new_with_borsh(
@@ -249,6 +603,7 @@ pub fn init_tokenizer(
 			principal_token_mint: *principal_token_mint,
 			yield_token_mint: *yield_token_mint,
 			expiry,
+			expiry_date,
 			fixed_apy,
 		},
 		vec![
@@ -259,6 +614,7 @@ pub fn init_tokenizer(
 			AccountMeta::new_readonly(spl_token::id(), false),
 			AccountMeta::new_readonly(system_program::id(), false),
 			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new_readonly(get_program_config_address().0, false),
 		],
 	))
 }
@@ -271,17 +627,19 @@ pub fn init_mints(
 	principal_token_mint: &Pubkey,
 	yield_token_mint: &Pubkey,
 	expiry: Expiry,
+	expiry_date: i64,
 ) -> Result<Instruction, ProgramError> {
 	Ok(Instruction::new_with_borsh(
 		crate::id(),
 		&TokenizerInstruction::InitializeMints {
 			underlying_mint: *underlying_mint,
 			expiry,
+			expiry_date,
 		},
 		vec![
 			AccountMeta::new(*lysergic_tokenizer, false),
 			AccountMeta::new(*authority, true),
-			AccountMeta::new(*underlying_mint, false),
+			AccountMeta::new_readonly(*underlying_mint, false),
 			AccountMeta::new(*principal_token_mint, false),
 			AccountMeta::new(*yield_token_mint, false),
 			AccountMeta::new_readonly(spl_token::id(), false),
@@ -291,6 +649,7 @@ pub fn init_mints(
 }
 
 /// Creates an `InitializeTokenizerAndMints` instruction
+#[allow(clippy::too_many_arguments)]
 pub fn init_tokenizer_and_mints(
 	tokenizer: &Pubkey,
 	authority: &Pubkey,
@@ -299,6 +658,7 @@ pub fn init_tokenizer_and_mints(
 	principal_token_mint: &Pubkey,
 	yield_token_mint: &Pubkey,
 	expiry: Expiry,
+	expiry_date: i64,
 	fixed_apy: u64,
 ) -> Result<Instruction, ProgramError> {
 	Ok(Instruction::new_with_borsh(
@@ -308,6 +668,7 @@ pub fn init_tokenizer_and_mints(
 			principal_token_mint: *principal_token_mint,
 			yield_token_mint: *yield_token_mint,
 			expiry,
+			expiry_date,
 			fixed_apy,
 		},
 		vec![
@@ -320,6 +681,7 @@ pub fn init_tokenizer_and_mints(
 			AccountMeta::new_readonly(spl_token::id(), false),
 			AccountMeta::new_readonly(system_program::id(), false),
 			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new_readonly(get_program_config_address().0, false),
 		],
 	))
 }
@@ -328,6 +690,8 @@ pub fn init_tokenizer_and_mints(
 pub fn deposit_underlying(
 	tokenizer: &Pubkey,
 	underlying_vault: &Pubkey,
+	principal_token_mint: &Pubkey,
+	yield_token_mint: &Pubkey,
 	user: &Pubkey,
 	user_underlying_token_account: &Pubkey,
 	amount: u64,
@@ -341,6 +705,10 @@ pub fn deposit_underlying(
 			AccountMeta::new(*user, true),
 			AccountMeta::new(*user_underlying_token_account, false),
 			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new(get_user_position_address(tokenizer, user).0, false),
+			AccountMeta::new_readonly(*principal_token_mint, false),
+			AccountMeta::new_readonly(*yield_token_mint, false),
 		],
 	))
 }
@@ -348,7 +716,9 @@ pub fn deposit_underlying(
 /// Creates a `TokenizePrincipal` instruction
 pub fn tokenize_principal(
 	lysergic_tokenizer: &Pubkey,
+	underlying_vault: &Pubkey,
 	principal_token_mint: &Pubkey,
+	yield_token_mint: &Pubkey,
 	user: &Pubkey,
 	user_principal_token_account: &Pubkey,
 	amount: u64,
@@ -364,6 +734,9 @@ pub fn tokenize_principal(
 			AccountMeta::new_readonly(spl_token::id(), false),
 			AccountMeta::new_readonly(system_program::id(), false),
 			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new(get_user_position_address(lysergic_tokenizer, user).0, false),
+			AccountMeta::new_readonly(*underlying_vault, false),
+			AccountMeta::new_readonly(*yield_token_mint, false),
 		],
 	))
 }
@@ -371,6 +744,8 @@ pub fn tokenize_principal(
 /// Creates a `TokenizeYield` instruction
 pub fn tokenize_yield(
 	lysergic_tokenizer: &Pubkey,
+	underlying_vault: &Pubkey,
+	principal_token_mint: &Pubkey,
 	yield_token_mint: &Pubkey,
 	user: &Pubkey,
 	user_yield_token_account: &Pubkey,
@@ -387,11 +762,15 @@ pub fn tokenize_yield(
 			AccountMeta::new_readonly(spl_token::id(), false),
 			AccountMeta::new_readonly(system_program::id(), false),
 			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new(get_user_position_address(lysergic_tokenizer, user).0, false),
+			AccountMeta::new_readonly(*underlying_vault, false),
+			AccountMeta::new_readonly(*principal_token_mint, false),
 		],
 	))
 }
 
 /// Creates a `DepositAndTokenize` instruction
+#[allow(clippy::too_many_arguments)]
 pub fn deposit_and_tokenize(
 	tokenizer: &Pubkey,
 	underlying_vault: &Pubkey,
@@ -418,24 +797,33 @@ pub fn deposit_and_tokenize(
 			AccountMeta::new_readonly(spl_token::id(), false),
 			AccountMeta::new_readonly(system_program::id(), false),
 			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new(get_user_position_address(tokenizer, user).0, false),
 		],
 	))
 }
 
 /// Creates a `RedeemPrincipal` instruction
+#[allow(clippy::too_many_arguments)]
 pub fn redeem_mature_principal(
 	tokenizer: &Pubkey,
 	underlying_vault: &Pubkey,
 	underlying_mint: &Pubkey,
 	principal_token_mint: &Pubkey,
+	yield_token_mint: &Pubkey,
 	user: &Pubkey,
 	user_underlying_token_account: &Pubkey,
 	user_principal_token_account: &Pubkey,
 	principal_amount: u64,
+	unwrap: bool,
+	close_principal_ata: bool,
 ) -> Result<Instruction, ProgramError> {
 	Ok(Instruction::new_with_borsh(
 		crate::id(),
-		&TokenizerInstruction::RedeemMaturePrincipal { principal_amount },
+		&TokenizerInstruction::RedeemMaturePrincipal {
+			principal_amount,
+			unwrap,
+			close_principal_ata,
+		},
 		vec![
 			AccountMeta::new(*tokenizer, false),
 			AccountMeta::new(*underlying_vault, false),
@@ -446,11 +834,14 @@ pub fn redeem_mature_principal(
 			AccountMeta::new(*user_principal_token_account, false),
 			AccountMeta::new_readonly(spl_token::id(), false),
 			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new_readonly(*yield_token_mint, false),
 		],
 	))
 }
 
 /// Creates a `RedeemPrincipalAndYield` instruction
+#[allow(clippy::too_many_arguments)]
 pub fn redeem_principal_and_yield(
 	tokenizer: &Pubkey,
 	underlying_vault: &Pubkey,
@@ -462,10 +853,18 @@ pub fn redeem_principal_and_yield(
 	user_principal_token_account: &Pubkey,
 	user_yield_token_account: &Pubkey,
 	amount: u64,
+	min_total_underlying_out: u64,
+	unwrap: bool,
+	redeem_order: RedeemOrder,
 ) -> Result<Instruction, ProgramError> {
 	Ok(Instruction::new_with_borsh(
 		crate::id(),
-		&TokenizerInstruction::RedeemPrincipalAndYield { amount },
+		&TokenizerInstruction::RedeemPrincipalAndYield {
+			amount,
+			min_total_underlying_out,
+			unwrap,
+			redeem_order,
+		},
 		vec![
 			AccountMeta::new(*tokenizer, false),
 			AccountMeta::new(*underlying_vault, false),
@@ -478,31 +877,90 @@ pub fn redeem_principal_and_yield(
 			AccountMeta::new(*user_yield_token_account, false),
 			AccountMeta::new_readonly(spl_token::id(), false),
 			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new(get_user_position_address(tokenizer, user).0, false),
+		],
+	))
+}
+
+/// Creates an `Unwind` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn unwind(
+	tokenizer: &Pubkey,
+	underlying_vault: &Pubkey,
+	underlying_mint: &Pubkey,
+	principal_token_mint: &Pubkey,
+	yield_token_mint: &Pubkey,
+	user: &Pubkey,
+	user_underlying_token_account: &Pubkey,
+	user_principal_token_account: &Pubkey,
+	user_yield_token_account: &Pubkey,
+	fraction_bps: u16,
+	min_total_underlying_out: u64,
+) -> Result<Instruction, ProgramError> {
+	if fraction_bps == 0 || fraction_bps as u64 > BPS_DENOMINATOR {
+		return Err(ProgramError::InvalidArgument);
+	}
+
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::Unwind {
+			fraction_bps,
+			min_total_underlying_out,
+		},
+		vec![
+			AccountMeta::new(*tokenizer, false),
+			AccountMeta::new(*underlying_vault, false),
+			AccountMeta::new_readonly(*underlying_mint, false),
+			AccountMeta::new(*principal_token_mint, false),
+			AccountMeta::new(*yield_token_mint, false),
+			AccountMeta::new(*user, true),
+			AccountMeta::new(*user_underlying_token_account, false),
+			AccountMeta::new(*user_principal_token_account, false),
+			AccountMeta::new(*user_yield_token_account, false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new(get_user_position_address(tokenizer, user).0, false),
 		],
 	))
 }
 
 /// Creates a `ClaimYield` instruction
+#[allow(clippy::too_many_arguments)]
 pub fn claim_yield(
 	tokenizer: &Pubkey,
 	underlying_vault: &Pubkey,
+	underlying_mint: &Pubkey,
+	principal_token_mint: &Pubkey,
 	yield_token_mint: &Pubkey,
 	user: &Pubkey,
 	user_underlying_token_account: &Pubkey,
 	user_yield_token_account: &Pubkey,
 	yield_amount: u64,
+	strict: bool,
+	unwrap: bool,
 ) -> Result<Instruction, ProgramError> {
 	Ok(Instruction::new_with_borsh(
 		crate::id(),
-		&TokenizerInstruction::ClaimYield { yield_amount },
+		&TokenizerInstruction::ClaimYield {
+			yield_amount,
+			strict,
+			unwrap,
+		},
 		vec![
 			AccountMeta::new(*tokenizer, false),
 			AccountMeta::new(*underlying_vault, false),
+			AccountMeta::new_readonly(*underlying_mint, false),
+			AccountMeta::new_readonly(*principal_token_mint, false),
 			AccountMeta::new(*yield_token_mint, false),
 			AccountMeta::new(*user, true),
 			AccountMeta::new(*user_underlying_token_account, false),
 			AccountMeta::new(*user_yield_token_account, false),
 			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+			AccountMeta::new(get_user_position_address(tokenizer, user).0, false),
 		],
 	))
 }
@@ -511,6 +969,7 @@ pub fn terminate(
 	tokenizer: &Pubkey,
 	authority: &Pubkey,
 	underlying_vault: &Pubkey,
+	underlying_mint: &Pubkey,
 	principal_token_mint: &Pubkey,
 	yield_token_mint: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
@@ -525,6 +984,15 @@ pub fn terminate(
 			AccountMeta::new(*yield_token_mint, false),
 			AccountMeta::new_readonly(spl_token::id(), false),
 			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(*underlying_mint, false),
+			AccountMeta::new(
+				spl_associated_token_account::get_associated_token_address(
+					authority,
+					underlying_mint,
+				),
+				false,
+			),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
 		],
 	))
 }
@@ -533,6 +1001,7 @@ pub fn terminate_tokenizer(
 	tokenizer: &Pubkey,
 	authority: &Pubkey,
 	underlying_vault: &Pubkey,
+	underlying_mint: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
 	Ok(Instruction::new_with_borsh(
 		crate::id(),
@@ -543,6 +1012,15 @@ pub fn terminate_tokenizer(
 			AccountMeta::new(*underlying_vault, false),
 			AccountMeta::new_readonly(spl_token::id(), false),
 			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(*underlying_mint, false),
+			AccountMeta::new(
+				spl_associated_token_account::get_associated_token_address(
+					authority,
+					underlying_mint,
+				),
+				false,
+			),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
 		],
 	))
 }
@@ -566,3 +1044,482 @@ pub fn terminate_mints(
 		],
 	))
 }
+
+/// Creates a `GetExchangeRate` instruction
+pub fn get_exchange_rate(tokenizer: &Pubkey) -> Result<Instruction, ProgramError> {
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::GetExchangeRate,
+		vec![AccountMeta::new_readonly(*tokenizer, false)],
+	))
+}
+
+/// Creates a `Reconcile` instruction
+pub fn reconcile(
+	tokenizer: &Pubkey,
+	underlying_vault: &Pubkey,
+	principal_token_mint: &Pubkey,
+	yield_token_mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::Reconcile,
+		vec![
+			AccountMeta::new_readonly(*tokenizer, false),
+			AccountMeta::new_readonly(*underlying_vault, false),
+			AccountMeta::new_readonly(*principal_token_mint, false),
+			AccountMeta::new_readonly(*yield_token_mint, false),
+		],
+	))
+}
+
+/// Creates a `RecreateVault` instruction
+pub fn recreate_vault(
+	tokenizer: &Pubkey,
+	authority: &Pubkey,
+	underlying_vault: &Pubkey,
+	underlying_mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::RecreateVault,
+		vec![
+			AccountMeta::new_readonly(*tokenizer, false),
+			AccountMeta::new(*authority, true),
+			AccountMeta::new(*underlying_vault, false),
+			AccountMeta::new_readonly(*underlying_mint, false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+		],
+	))
+}
+
+/// Creates an `UpdateFixedApy` instruction
+pub fn update_fixed_apy(
+	tokenizer: &Pubkey,
+	authority: &Pubkey,
+	principal_token_mint: &Pubkey,
+	yield_token_mint: &Pubkey,
+	fixed_apy: u64,
+) -> Result<Instruction, ProgramError> {
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::UpdateFixedApy { fixed_apy },
+		vec![
+			AccountMeta::new(*tokenizer, false),
+			AccountMeta::new_readonly(*authority, true),
+			AccountMeta::new_readonly(*principal_token_mint, false),
+			AccountMeta::new_readonly(*yield_token_mint, false),
+		],
+	))
+}
+
+/// Creates a `TerminatePrincipalMint` instruction
+pub fn terminate_principal_mint(
+	tokenizer: &Pubkey,
+	authority: &Pubkey,
+	principal_token_mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::TerminatePrincipalMint,
+		vec![
+			AccountMeta::new(*tokenizer, false),
+			AccountMeta::new(*authority, true),
+			AccountMeta::new(*principal_token_mint, false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+		],
+	))
+}
+
+/// Creates a `BatchRedeem` instruction from `(user, principal_amount, yield_amount)` entries,
+/// using each user's associated token accounts
+pub fn batch_redeem(
+	tokenizer: &Pubkey,
+	underlying_vault: &Pubkey,
+	underlying_mint: &Pubkey,
+	principal_token_mint: &Pubkey,
+	yield_token_mint: &Pubkey,
+	entries: &[(Pubkey, u64, u64)],
+) -> Result<Instruction, ProgramError> {
+	let mut accounts = vec![
+		AccountMeta::new(*tokenizer, false),
+		AccountMeta::new(*underlying_vault, false),
+		AccountMeta::new_readonly(*underlying_mint, false),
+		AccountMeta::new(*principal_token_mint, false),
+		AccountMeta::new(*yield_token_mint, false),
+		AccountMeta::new_readonly(spl_token::id(), false),
+		AccountMeta::new_readonly(system_program::id(), false),
+		AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+	];
+
+	for (user, _, _) in entries {
+		accounts.extend([
+			AccountMeta::new(*user, true),
+			AccountMeta::new(
+				spl_associated_token_account::get_associated_token_address(user, underlying_mint),
+				false,
+			),
+			AccountMeta::new(
+				spl_associated_token_account::get_associated_token_address(
+					user,
+					principal_token_mint,
+				),
+				false,
+			),
+			AccountMeta::new(
+				spl_associated_token_account::get_associated_token_address(user, yield_token_mint),
+				false,
+			),
+			AccountMeta::new(get_user_position_address(tokenizer, user).0, false),
+		]);
+	}
+
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::BatchRedeem {
+			entries: entries
+				.iter()
+				.map(|(_, principal_amount, yield_amount)| (*principal_amount, *yield_amount))
+				.collect(),
+		},
+		accounts,
+	))
+}
+
+/// Creates an `InitConfig` instruction
+pub fn init_config(
+	admin: &Pubkey,
+	min_tenor: i64,
+	max_tenor: i64,
+) -> Result<Instruction, ProgramError> {
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::InitConfig {
+			min_tenor,
+			max_tenor,
+		},
+		vec![
+			AccountMeta::new(get_program_config_address().0, false),
+			AccountMeta::new(*admin, true),
+			AccountMeta::new_readonly(
+				bpf_loader_upgradeable::get_program_data_address(&crate::id()),
+				false,
+			),
+			AccountMeta::new_readonly(system_program::id(), false),
+		],
+	))
+}
+
+/// Creates an `UpdateMinHold` instruction
+pub fn update_min_hold(
+	tokenizer: &Pubkey,
+	authority: &Pubkey,
+	min_hold_seconds: i64,
+) -> Result<Instruction, ProgramError> {
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::UpdateMinHold { min_hold_seconds },
+		vec![
+			AccountMeta::new(*tokenizer, false),
+			AccountMeta::new_readonly(*authority, true),
+		],
+	))
+}
+
+/// Creates an `UpdateRebasing` instruction
+pub fn update_rebasing(
+	tokenizer: &Pubkey,
+	authority: &Pubkey,
+	principal_token_mint: &Pubkey,
+	yield_token_mint: &Pubkey,
+	rebasing: bool,
+) -> Result<Instruction, ProgramError> {
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::UpdateRebasing { rebasing },
+		vec![
+			AccountMeta::new(*tokenizer, false),
+			AccountMeta::new_readonly(*authority, true),
+			AccountMeta::new_readonly(*principal_token_mint, false),
+			AccountMeta::new_readonly(*yield_token_mint, false),
+		],
+	))
+}
+
+/// Creates an `UpdateConfig` instruction
+pub fn update_config(
+	admin: &Pubkey,
+	min_tenor: i64,
+	max_tenor: i64,
+) -> Result<Instruction, ProgramError> {
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::UpdateConfig {
+			min_tenor,
+			max_tenor,
+		},
+		vec![
+			AccountMeta::new(get_program_config_address().0, false),
+			AccountMeta::new_readonly(*admin, true),
+		],
+	))
+}
+
+/// Creates an `InitializeAmm` instruction
+pub fn initialize_amm(
+	tokenizer: &Pubkey,
+	authority: &Pubkey,
+	principal_token_mint: &Pubkey,
+	underlying_mint: &Pubkey,
+	fee_bps: u16,
+) -> Result<Instruction, ProgramError> {
+	if fee_bps > MAX_POOL_FEE_BPS {
+		return Err(ProgramError::InvalidArgument);
+	}
+
+	let (pool, _) = get_pool_address(tokenizer);
+
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::InitializeAmm { fee_bps },
+		vec![
+			AccountMeta::new(pool, false),
+			AccountMeta::new_readonly(*tokenizer, false),
+			AccountMeta::new(*authority, true),
+			AccountMeta::new(get_lp_mint_address(&pool).0, false),
+			AccountMeta::new(
+				spl_associated_token_account::get_associated_token_address(
+					&pool,
+					principal_token_mint,
+				),
+				false,
+			),
+			AccountMeta::new(
+				spl_associated_token_account::get_associated_token_address(&pool, underlying_mint),
+				false,
+			),
+			AccountMeta::new_readonly(*principal_token_mint, false),
+			AccountMeta::new_readonly(*underlying_mint, false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+		],
+	))
+}
+
+// The pool's and user's token accounts, in the order the pool instructions expect them
+fn pool_accounts(
+	tokenizer: &Pubkey,
+	principal_token_mint: &Pubkey,
+	underlying_mint: &Pubkey,
+	user: &Pubkey,
+) -> (Pubkey, [Pubkey; 4]) {
+	let (pool, _) = get_pool_address(tokenizer);
+
+	(
+		pool,
+		[
+			spl_associated_token_account::get_associated_token_address(&pool, principal_token_mint),
+			spl_associated_token_account::get_associated_token_address(&pool, underlying_mint),
+			spl_associated_token_account::get_associated_token_address(user, principal_token_mint),
+			spl_associated_token_account::get_associated_token_address(user, underlying_mint),
+		],
+	)
+}
+
+/// Creates an `AddLiquidity` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn add_liquidity(
+	tokenizer: &Pubkey,
+	principal_token_mint: &Pubkey,
+	underlying_mint: &Pubkey,
+	user: &Pubkey,
+	principal_amount: u64,
+	underlying_amount: u64,
+	min_lp_out: u64,
+) -> Result<Instruction, ProgramError> {
+	let (pool, [pool_principal, pool_underlying, user_principal, user_underlying]) =
+		pool_accounts(tokenizer, principal_token_mint, underlying_mint, user);
+	let (lp_mint, _) = get_lp_mint_address(&pool);
+
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::AddLiquidity {
+			principal_amount,
+			underlying_amount,
+			min_lp_out,
+		},
+		vec![
+			AccountMeta::new(pool, false),
+			AccountMeta::new(pool_principal, false),
+			AccountMeta::new(pool_underlying, false),
+			AccountMeta::new(lp_mint, false),
+			AccountMeta::new(*user, true),
+			AccountMeta::new(user_principal, false),
+			AccountMeta::new(user_underlying, false),
+			AccountMeta::new(
+				spl_associated_token_account::get_associated_token_address(user, &lp_mint),
+				false,
+			),
+			AccountMeta::new_readonly(spl_token::id(), false),
+			AccountMeta::new_readonly(system_program::id(), false),
+			AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+		],
+	))
+}
+
+/// Creates a `RemoveLiquidity` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn remove_liquidity(
+	tokenizer: &Pubkey,
+	principal_token_mint: &Pubkey,
+	underlying_mint: &Pubkey,
+	user: &Pubkey,
+	lp_amount: u64,
+	min_principal_out: u64,
+	min_underlying_out: u64,
+) -> Result<Instruction, ProgramError> {
+	let (pool, [pool_principal, pool_underlying, user_principal, user_underlying]) =
+		pool_accounts(tokenizer, principal_token_mint, underlying_mint, user);
+	let (lp_mint, _) = get_lp_mint_address(&pool);
+
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::RemoveLiquidity {
+			lp_amount,
+			min_principal_out,
+			min_underlying_out,
+		},
+		vec![
+			AccountMeta::new(pool, false),
+			AccountMeta::new(pool_principal, false),
+			AccountMeta::new(pool_underlying, false),
+			AccountMeta::new(lp_mint, false),
+			AccountMeta::new_readonly(*user, true),
+			AccountMeta::new(user_principal, false),
+			AccountMeta::new(user_underlying, false),
+			AccountMeta::new(
+				spl_associated_token_account::get_associated_token_address(user, &lp_mint),
+				false,
+			),
+			AccountMeta::new_readonly(spl_token::id(), false),
+		],
+	))
+}
+
+/// Creates a `Swap` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+	tokenizer: &Pubkey,
+	principal_token_mint: &Pubkey,
+	underlying_mint: &Pubkey,
+	user: &Pubkey,
+	principal_in: bool,
+	amount_in: u64,
+	min_amount_out: u64,
+) -> Result<Instruction, ProgramError> {
+	let (pool, [pool_principal, pool_underlying, user_principal, user_underlying]) =
+		pool_accounts(tokenizer, principal_token_mint, underlying_mint, user);
+
+	Ok(Instruction::new_with_borsh(
+		crate::id(),
+		&TokenizerInstruction::Swap {
+			principal_in,
+			amount_in,
+			min_amount_out,
+		},
+		vec![
+			AccountMeta::new(pool, false),
+			AccountMeta::new(pool_principal, false),
+			AccountMeta::new(pool_underlying, false),
+			AccountMeta::new_readonly(*user, true),
+			AccountMeta::new(user_principal, false),
+			AccountMeta::new(user_underlying, false),
+			AccountMeta::new_readonly(spl_token::id(), false),
+		],
+	))
+}
+
+/// Parameters of a new tokenizer, from which the full initialization sequence is built
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenizerConfig {
+	pub authority: Pubkey,
+	pub underlying_mint: Pubkey,
+	pub expiry: Expiry,
+	/// The fixed APY of the Principal token in basis points
+	pub fixed_apy: u64,
+}
+
+impl TokenizerConfig {
+	pub fn new(authority: Pubkey, underlying_mint: Pubkey, expiry: Expiry) -> Self {
+		Self {
+			authority,
+			underlying_mint,
+			expiry,
+			fixed_apy: 0,
+		}
+	}
+
+	pub fn fixed_apy(mut self, fixed_apy: u64) -> Self {
+		self.fixed_apy = fixed_apy;
+		self
+	}
+
+	/// Rejects a fixed APY above `BPS_DENOMINATOR` (100%) and a default underlying mint or
+	/// authority before any instruction is built
+	pub fn validate(&self) -> Result<(), ProgramError> {
+		if self.fixed_apy > BPS_DENOMINATOR {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		if self.underlying_mint == Pubkey::default() || self.authority == Pubkey::default() {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		Ok(())
+	}
+
+	/// Builds the instructions initializing the tokenizer and then its mints, with the expiry
+	/// date resolved from `now`. The mints are PDAs created by the program, so no separate
+	/// account creation is needed.
+	pub fn instructions(&self, now: i64) -> Result<Vec<Instruction>, ProgramError> {
+		self.validate()?;
+
+		let expiry_date = self
+			.expiry
+			.to_expiry_date(now)
+			.ok_or(ProgramError::InvalidArgument)?;
+		let (tokenizer, _) = derive_tokenizer(&self.underlying_mint, expiry_date)?;
+		let underlying_vault = spl_associated_token_account::get_associated_token_address(
+			&tokenizer,
+			&self.underlying_mint,
+		);
+		let (principal_token_mint, _) = get_principal_mint_address(&tokenizer);
+		let (yield_token_mint, _) = get_yield_mint_address(&tokenizer);
+
+		Ok(vec![
+			init_tokenizer(
+				&tokenizer,
+				&self.authority,
+				&underlying_vault,
+				&self.underlying_mint,
+				&principal_token_mint,
+				&yield_token_mint,
+				self.expiry.clone(),
+				expiry_date,
+				self.fixed_apy,
+			)?,
+			init_mints(
+				&tokenizer,
+				&self.authority,
+				&self.underlying_mint,
+				&principal_token_mint,
+				&yield_token_mint,
+				self.expiry.clone(),
+				expiry_date,
+			)?,
+		])
+	}
+}
@@ -1,16 +1,40 @@
 pub mod entrypoint;
 pub mod error;
 pub mod instruction;
+pub mod pool;
 pub mod processor;
+pub mod spl_utils;
 pub mod state;
 
 use {
 	borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
-	solana_program::{declare_id, program_error::ProgramError, pubkey::Pubkey},
+	solana_program::{
+		declare_id, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+	},
 };
 
 declare_id!("LSDjBzV1CdC4zeXETyLnoUddeBeQAvXXRo49j8rSguH");
 
+// Fixed-point scale of the exchange rate, i.e. a rate of `RATE_SCALE` is 1:1
+pub const RATE_SCALE: u64 = 1_000_000_000;
+
+// Upper bound on how far the fixed APY may discount the principal over its tenor, i.e. on the
+// principal a single underlying buys at the exchange rate
+pub const MAX_PRINCIPAL_PER_UNDERLYING: u64 = 4;
+
+// Upper bound on `BatchRedeem` entries. Each entry adds a signature and five accounts, so no
+// more than three fit in a legacy transaction alongside a separate fee payer.
+pub const MAX_BATCH_REDEEM_ENTRIES: usize = 3;
+
+const SECONDS_PER_YEAR: i64 = 31536000;
+// A month is a twelfth of a 365 day year, so the standard tenors are whole months
+const SECONDS_PER_MONTH: i64 = SECONDS_PER_YEAR / 12;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// Longest tenor, in months, the program supports; the program config can narrow it down
+pub const MAX_TENOR_MONTHS: i64 = 60;
+const BPS_DENOMINATOR: u64 = 10_000;
+
 // Generate the tokenizer address
 pub fn get_tokenizer_address(underlying_mint: &Pubkey, expiry_date: i64) -> (Pubkey, u8) {
 	let seeds = &[
@@ -21,6 +45,19 @@ pub fn get_tokenizer_address(underlying_mint: &Pubkey, expiry_date: i64) -> (Pub
 	Pubkey::find_program_address(seeds, &crate::id())
 }
 
+// Generate the tokenizer address, rejecting expiry dates that no valid tenor can produce,
+// i.e. dates at or before the epoch or not at the start of a day
+pub fn derive_tokenizer(
+	underlying_mint: &Pubkey,
+	expiry_date: i64,
+) -> Result<(Pubkey, u8), ProgramError> {
+	if expiry_date <= 0 || expiry_date % (24 * 60 * 60) != 0 {
+		return Err(ProgramError::InvalidArgument);
+	}
+
+	Ok(get_tokenizer_address(underlying_mint, expiry_date))
+}
+
 // Generate the principal mint address
 pub fn get_principal_mint_address(tokenizer_address: &Pubkey) -> (Pubkey, u8) {
 	let seeds = &[b"principal", &tokenizer_address.to_bytes()[..]];
@@ -33,11 +70,56 @@ pub fn get_yield_mint_address(tokenizer_address: &Pubkey) -> (Pubkey, u8) {
 	Pubkey::find_program_address(seeds, &crate::id())
 }
 
-#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
+// Generate the address of a user's position in a tokenizer
+pub fn get_user_position_address(tokenizer_address: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+	let seeds = &[
+		b"position",
+		&tokenizer_address.to_bytes()[..],
+		&user.to_bytes()[..],
+	];
+	Pubkey::find_program_address(seeds, &crate::id())
+}
+
+// Generate the program config address
+pub fn get_program_config_address() -> (Pubkey, u8) {
+	Pubkey::find_program_address(&[b"config"], &crate::id())
+}
+
+// Generate the address of a tokenizer's principal token AMM pool
+pub fn get_pool_address(tokenizer_address: &Pubkey) -> (Pubkey, u8) {
+	let seeds = &[b"pool", &tokenizer_address.to_bytes()[..]];
+	Pubkey::find_program_address(seeds, &crate::id())
+}
+
+// Generate the address of a pool's LP token mint
+pub fn get_lp_mint_address(pool_address: &Pubkey) -> (Pubkey, u8) {
+	let seeds = &[b"lp", &pool_address.to_bytes()[..]];
+	Pubkey::find_program_address(seeds, &crate::id())
+}
+
+// Ordered by `to_seconds`, see the `Ord` impl below
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq, Hash)]
 pub enum Expiry {
 	TwelveMonths,
 	EighteenMonths,
 	TwentyFourMonths,
+	// Any other duration, in seconds
+	Custom(i64),
+}
+
+impl Ord for Expiry {
+	// Shortest first, with a standard tenor ahead of a custom one of the same length so the
+	// ordering stays consistent with `Eq`
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		(self.to_seconds(), matches!(self, Expiry::Custom(_)))
+			.cmp(&(other.to_seconds(), matches!(other, Expiry::Custom(_))))
+	}
+}
+
+impl PartialOrd for Expiry {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
 }
 
 impl Expiry {
@@ -46,25 +128,374 @@ impl Expiry {
 			Expiry::TwelveMonths => 31536000,
 			Expiry::EighteenMonths => 47304000,
 			Expiry::TwentyFourMonths => 63072000,
+			Expiry::Custom(seconds) => *seconds,
 		}
 	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Expiry::TwelveMonths => "12m",
+			Expiry::EighteenMonths => "18m",
+			Expiry::TwentyFourMonths => "24m",
+			Expiry::Custom(_) => "custom",
+		}
+	}
+
+	// Whole months, rounded down for custom durations
+	pub fn to_months(&self) -> i64 {
+		match self {
+			Expiry::TwelveMonths => 12,
+			Expiry::EighteenMonths => 18,
+			Expiry::TwentyFourMonths => 24,
+			Expiry::Custom(seconds) => seconds / SECONDS_PER_MONTH,
+		}
+	}
+
+	// Single place deciding which tenors, in months, the program supports; the program
+	// config can narrow these down further
+	pub fn validate_tenor(months: i64) -> Result<(), ProgramError> {
+		if !(1..=MAX_TENOR_MONTHS).contains(&months) {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		Ok(())
+	}
+
+	// Custom durations must be at least a day, so the day-aligned expiry date lies in the
+	// future, and no longer than the longest supported tenor
+	pub fn validate(&self) -> Result<(), ProgramError> {
+		let Expiry::Custom(seconds) = self else {
+			return Ok(());
+		};
+
+		if *seconds < SECONDS_PER_DAY || *seconds > MAX_TENOR_MONTHS * SECONDS_PER_MONTH {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		Ok(())
+	}
+
+	// Months to a tenor, any supported month count other than the standard tenors becomes a
+	// custom duration
 	pub fn from_i64(expiry: i64) -> Result<Self, ProgramError> {
+		Self::validate_tenor(expiry)?;
+
 		match expiry {
 			12 => Ok(Expiry::TwelveMonths),
 			18 => Ok(Expiry::EighteenMonths),
 			24 => Ok(Expiry::TwentyFourMonths),
-			_ => Err(ProgramError::InvalidArgument),
+			months => Ok(Expiry::Custom(months * SECONDS_PER_MONTH)),
 		}
 	}
 
+	// A custom duration in seconds
+	pub fn custom(seconds: i64) -> Result<Self, ProgramError> {
+		let expiry = Expiry::Custom(seconds);
+		expiry.validate()?;
+
+		Ok(expiry)
+	}
+
 	// We set the expiry date to the beginning of the day of the expiry date
 	// Handling a `None` expiry date is the responsibility of the calling program
 	// since this function is used both on-chain and off-chain and thus requires different
 	// methods to handle the `None` case in each context.
 	pub fn to_expiry_date(&self, ts: i64) -> Option<i64> {
+		self.validate().ok()?;
 		let expiry_seconds = self.to_seconds();
-		let expiry_timestamp = ts + expiry_seconds;
-		let days = expiry_timestamp / (24 * 60 * 60);
-		Some(days * 24 * 60 * 60)
+		let expiry_timestamp = ts.checked_add(expiry_seconds)?;
+		// Floor rather than truncate so pre-epoch timestamps also round down to their day
+		let days = expiry_timestamp.div_euclid(24 * 60 * 60);
+		days.checked_mul(24 * 60 * 60)
+	}
+
+	// The expiry date is resolved off-chain and passed in with the instruction, as the client
+	// and the cluster may read their timestamps either side of midnight. We accept any
+	// day-aligned date within a day of the one computed from `ts` for this tenor.
+	pub fn is_valid_expiry_date(&self, ts: i64, expiry_date: i64) -> bool {
+		let Some(computed_expiry_date) = self.to_expiry_date(ts) else {
+			return false;
+		};
+
+		expiry_date % (24 * 60 * 60) == 0
+			&& (expiry_date - computed_expiry_date).abs() <= 24 * 60 * 60
+	}
+}
+
+// Recovers the tenor a tokenizer was created with from its creation timestamp and stored
+// expiry date, within the same one day tolerance the program accepts. Returns `None` for dates
+// that match no standard tenor, including custom ones.
+pub fn expiry_tenor_from_dates(created_ts: i64, expiry_date: i64) -> Option<Expiry> {
+	[
+		Expiry::TwelveMonths,
+		Expiry::EighteenMonths,
+		Expiry::TwentyFourMonths,
+	]
+	.into_iter()
+	.find(|expiry| expiry.is_valid_expiry_date(created_ts, expiry_date))
+}
+
+// Formats an expiry date as `YYYY-MM-DD` (UTC) for display, e.g. in token names. Works from
+// the day count alone so it can be used on-chain without a date library.
+pub fn expiry_label(expiry_date: i64) -> String {
+	// Civil-from-days conversion, shifted so years start in March and leap days fall last
+	let days = expiry_date.div_euclid(24 * 60 * 60) + 719468;
+	let era = days.div_euclid(146097);
+	let day_of_era = days.rem_euclid(146097);
+	let year_of_era =
+		(day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let shifted_month = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+	let month = if shifted_month < 10 {
+		shifted_month + 3
+	} else {
+		shifted_month - 9
+	};
+	let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+	format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Rounding policy: every amount paid out of the vault is rounded down and every amount owed
+// to the vault is rounded up, so repeated rounding can never leave the vault holding less
+// than its outstanding obligations. Both helpers multiply in `u128` and return `None` on
+// division by zero or if the result does not fit in a `u64`.
+pub fn mul_div_floor(a: u64, b: u64, denominator: u64) -> Option<u64> {
+	if denominator == 0 {
+		return None;
+	}
+
+	u64::try_from(a as u128 * b as u128 / denominator as u128).ok()
+}
+
+pub fn mul_div_ceil(a: u64, b: u64, denominator: u64) -> Option<u64> {
+	if denominator == 0 {
+		return None;
+	}
+
+	u64::try_from((a as u128 * b as u128).div_ceil(denominator as u128)).ok()
+}
+
+// Underlying per principal token, scaled by `RATE_SCALE`. The principal token is discounted
+// by the fixed APY (in basis points) over the time remaining until expiry, using simple
+// interest, and converges to 1:1 at maturity. This is a payout rate so it rounds down.
+pub fn exchange_rate(fixed_apy: u64, expiry_date: i64, now: i64) -> Option<u64> {
+	let remaining = expiry_date.saturating_sub(now).max(0) as u64;
+	let year = BPS_DENOMINATOR * SECONDS_PER_YEAR as u64;
+	let discount = year.checked_add(fixed_apy.checked_mul(remaining)?)?;
+
+	mul_div_floor(RATE_SCALE, year, discount)
+}
+
+// Rejects a fixed APY that discounts the principal so steeply, or that overflows, that a
+// single underlying would buy more than `MAX_PRINCIPAL_PER_UNDERLYING` principal
+pub fn validate_fixed_apy(fixed_apy: u64, expiry_date: i64, now: i64) -> Result<(), ProgramError> {
+	let rate = exchange_rate(fixed_apy, expiry_date, now).ok_or(ProgramError::InvalidArgument)?;
+
+	if rate < RATE_SCALE / MAX_PRINCIPAL_PER_UNDERLYING {
+		return Err(ProgramError::InvalidArgument);
+	}
+
+	Ok(())
+}
+
+// Underlying paid out for a claim of `amount` yield tokens. Only the vault balance in excess
+// of the principal supply is claimable, as that part must stay to redeem principal 1:1.
+// Shared by `ClaimYield` and off-chain previews so both always agree.
+pub fn simulate_claim_yield(vault_balance: u64, principal_supply: u64, amount: u64) -> u64 {
+	amount.min(vault_balance.saturating_sub(principal_supply))
+}
+
+// Underlying paid out for redeeming `amount` principal. Principal is redeemed 1:1, unless the
+// underlying rebases, in which case it is redeemed for its share of the vault, capped at the
+// principal supply and rounded down as a payout. The cap leaves a rebase gain to the yield
+// holders, as with any other surplus, while a rebase loss is shared by the principal holders.
+// Shared by the redemption handlers and off-chain previews.
+pub fn simulate_redeem_principal(
+	vault_balance: u64,
+	principal_supply: u64,
+	amount: u64,
+	rebasing: bool,
+) -> Option<u64> {
+	if !rebasing {
+		return Some(amount);
+	}
+
+	mul_div_floor(
+		amount,
+		vault_balance.min(principal_supply),
+		principal_supply,
+	)
+}
+
+// Lamports needed to rent-exempt every account created when initializing a tokenizer and its
+// mints: the tokenizer itself, its underlying vault and the principal and yield mints. Init
+// only accepts SPL Token, so any other token program is rejected rather than sized as SPL Token.
+pub fn init_rent(rent: &Rent, token_program: &Pubkey) -> Result<u64, ProgramError> {
+	if token_program != &spl_token::id() {
+		return Err(ProgramError::IncorrectProgramId);
+	}
+
+	Ok(rent
+		.minimum_balance(state::STATE_SIZE)
+		.saturating_add(rent.minimum_balance(spl_token::state::Account::LEN))
+		.saturating_add(
+			rent.minimum_balance(spl_token::state::Mint::LEN)
+				.saturating_mul(2),
+		))
+}
+
+#[cfg(test)]
+mod tests {
+	use {super::*, proptest::prelude::*};
+
+	#[test]
+	fn mul_div_rounds_down_and_up() {
+		assert_eq!(mul_div_floor(10, 10, 3), Some(33));
+		assert_eq!(mul_div_ceil(10, 10, 3), Some(34));
+		assert_eq!(mul_div_floor(10, 10, 5), Some(20));
+		assert_eq!(mul_div_ceil(10, 10, 5), Some(20));
+		// The product is taken in u128, only the result has to fit in a u64
+		assert_eq!(mul_div_floor(u64::MAX, u64::MAX, u64::MAX), Some(u64::MAX));
+		assert_eq!(mul_div_ceil(u64::MAX, 2, 2), Some(u64::MAX));
+		assert_eq!(mul_div_floor(u64::MAX, 2, 1), None);
+		assert_eq!(mul_div_floor(1, 1, 0), None);
+		assert_eq!(mul_div_ceil(1, 1, 0), None);
+	}
+
+	proptest! {
+		#[test]
+		fn mul_div_brackets_the_exact_result(a: u64, b: u64, denominator in 1..=u64::MAX) {
+			let exact = a as u128 * b as u128;
+
+			if let Some(floor) = mul_div_floor(a, b, denominator) {
+				prop_assert!(floor as u128 * denominator as u128 <= exact);
+				prop_assert!((floor as u128 + 1) * denominator as u128 > exact);
+			}
+
+			if let (Some(floor), Some(ceil)) =
+				(mul_div_floor(a, b, denominator), mul_div_ceil(a, b, denominator))
+			{
+				prop_assert!(ceil - floor <= 1);
+				prop_assert_eq!(ceil == floor, exact.is_multiple_of(denominator as u128));
+			}
+		}
+
+		// Redeeming a pro rata vault piece by piece, each payout rounded down, never pays out
+		// more than the vault holds and never leaves the remaining holders worse off
+		#[test]
+		fn repeated_rounding_cannot_drain_the_vault(
+			vault in 0..=1_000_000_000_000u64,
+			pieces in prop::collection::vec(1..=1_000_000u64, 1..20),
+		) {
+			let mut supply: u64 = pieces.iter().sum();
+			let mut vault = vault;
+
+			for piece in pieces {
+				let payout = mul_div_floor(piece, vault, supply).unwrap();
+				prop_assert!(payout <= vault);
+
+				let (remaining_vault, remaining_supply) = (vault - payout, supply - piece);
+				prop_assert!(
+					remaining_vault as u128 * supply as u128
+						>= vault as u128 * remaining_supply as u128
+				);
+				(vault, supply) = (remaining_vault, remaining_supply);
+			}
+
+			prop_assert_eq!(supply, 0);
+		}
+	}
+
+	#[test]
+	fn expiry_label_formats_known_dates() {
+		assert_eq!(expiry_label(0), "1970-01-01");
+		assert_eq!(expiry_label(951_782_400), "2000-02-29");
+		assert_eq!(expiry_label(1_735_603_200), "2024-12-31");
+		assert_eq!(expiry_label(1_767_225_600), "2026-01-01");
+		// 2100 is not a leap year
+		assert_eq!(expiry_label(4_107_542_400), "2100-03-01");
+		// Any time of day formats as its date
+		assert_eq!(expiry_label(1_767_225_600 - 1), "2025-12-31");
+	}
+
+	#[test]
+	fn exchange_rate_converges_to_one_at_maturity() {
+		let expiry_date = SECONDS_PER_YEAR;
+		let rates: Vec<u64> = [
+			0,
+			SECONDS_PER_YEAR / 2,
+			SECONDS_PER_YEAR - 1,
+			SECONDS_PER_YEAR,
+		]
+		.iter()
+		.map(|now| exchange_rate(1_000, expiry_date, *now).unwrap())
+		.collect();
+
+		// A year out at 10% the principal is worth 1 / 1.1 underlying, rounded down
+		assert_eq!(rates[0], 909_090_909);
+		assert!(rates.windows(2).all(|pair| pair[0] < pair[1]));
+		assert_eq!(rates[3], RATE_SCALE);
+		assert_eq!(
+			exchange_rate(1_000, expiry_date, expiry_date + 1),
+			Some(RATE_SCALE)
+		);
+		assert_eq!(exchange_rate(0, expiry_date, 0), Some(RATE_SCALE));
+	}
+
+	#[test]
+	fn claim_yield_is_capped_to_the_surplus() {
+		assert_eq!(simulate_claim_yield(1_100, 1_000, 50), 50);
+		assert_eq!(simulate_claim_yield(1_100, 1_000, 500), 100);
+		assert_eq!(simulate_claim_yield(1_000, 1_000, 500), 0);
+		assert_eq!(simulate_claim_yield(900, 1_000, 500), 0);
+	}
+
+	#[test]
+	fn redeem_principal_is_one_to_one_without_rebasing() {
+		assert_eq!(simulate_redeem_principal(900, 1_000, 500, false), Some(500));
+		assert_eq!(
+			simulate_redeem_principal(1_100, 1_000, 500, false),
+			Some(500)
+		);
+	}
+
+	#[test]
+	fn redeem_principal_shares_rebase_losses_only() {
+		assert_eq!(simulate_redeem_principal(900, 1_000, 500, true), Some(450));
+		assert_eq!(
+			simulate_redeem_principal(1_100, 1_000, 500, true),
+			Some(500)
+		);
+		// Rounded down as a payout
+		assert_eq!(simulate_redeem_principal(999, 1_000, 3, true), Some(2));
+		assert_eq!(simulate_redeem_principal(0, 0, 0, true), None);
+	}
+
+	#[test]
+	fn init_rent_sums_each_account() {
+		for rent in [
+			Rent::default(),
+			Rent {
+				lamports_per_byte_year: 1,
+				exemption_threshold: 1.0,
+				burn_percent: 0,
+			},
+		] {
+			// Sizes spelled out, so a change to any account's size shows up here
+			let tokenizer = rent.minimum_balance(246);
+			let vault = rent.minimum_balance(165);
+			let mint = rent.minimum_balance(82);
+
+			assert_eq!(
+				init_rent(&rent, &spl_token::id()),
+				Ok(tokenizer + vault + 2 * mint)
+			);
+			assert_eq!(
+				init_rent(&rent, &Pubkey::new_unique()),
+				Err(ProgramError::IncorrectProgramId)
+			);
+		}
 	}
 }
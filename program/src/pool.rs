@@ -0,0 +1,171 @@
+use {
+	crate::error::TokenizerError,
+	borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
+	solana_program::{program_error::ProgramError, pubkey::Pubkey},
+};
+
+pub const POOL_SIZE: usize = 1 + 1 + 32 + 32 + 32 + 32 + 2 + 8 + 8 + 32; // 180 bytes
+
+// Fees are charged on the input of a swap, in basis points
+pub const MAX_POOL_FEE_BPS: u16 = 1_000;
+const FEE_DENOMINATOR: u64 = 10_000;
+
+// A constant-product market between a tokenizer's principal token and its underlying, at
+// `get_pool_address`. Its token accounts are the pool's associated token accounts, and the
+// reserves are tracked here rather than read from them so donations cannot move the price.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+pub struct PoolState {
+	pub is_initialized: bool,
+	pub bump: u8,
+	pub tokenizer: Pubkey,
+	pub principal_token_mint: Pubkey,
+	pub underlying_mint: Pubkey,
+	pub lp_mint: Pubkey,
+	pub fee_bps: u16,
+	pub principal_reserve: u64,
+	pub underlying_reserve: u64,
+	// The tokenizer's token program, which owns the pool's token accounts
+	pub token_program: Pubkey,
+}
+
+impl PoolState {
+	pub fn unpack_initialized(data: &[u8]) -> Result<Self, ProgramError> {
+		let pool = Self::try_from_slice(data)?;
+
+		if !pool.is_initialized {
+			return Err(TokenizerError::PoolNotInitialized.into());
+		}
+
+		Ok(pool)
+	}
+}
+
+// All pool math rounds in favour of the pool: amounts paid out round down and amounts taken
+// in round up, so no sequence of calls can extract more than was put in.
+
+// LP tokens minted for depositing up to `principal` and `underlying`, and the amounts of each
+// actually taken. The first deposit sets the price and mints the geometric mean; later
+// deposits mint for whichever side is smaller relative to the reserves and only take the
+// matching amount of the other side, rounded up.
+pub fn deposit_amounts(
+	principal: u64,
+	underlying: u64,
+	principal_reserve: u64,
+	underlying_reserve: u64,
+	lp_supply: u64,
+) -> Option<(u64, u64, u64)> {
+	if lp_supply == 0 {
+		let lp = u64::try_from(isqrt(principal as u128 * underlying as u128)).ok()?;
+		return Some((lp, principal, underlying));
+	}
+
+	let lp = crate::mul_div_floor(principal, lp_supply, principal_reserve)?.min(
+		crate::mul_div_floor(underlying, lp_supply, underlying_reserve)?,
+	);
+
+	Some((
+		lp,
+		crate::mul_div_ceil(lp, principal_reserve, lp_supply)?,
+		crate::mul_div_ceil(lp, underlying_reserve, lp_supply)?,
+	))
+}
+
+// Principal and underlying paid out for burning `lp` LP tokens
+pub fn amounts_for_withdrawal(
+	lp: u64,
+	principal_reserve: u64,
+	underlying_reserve: u64,
+	lp_supply: u64,
+) -> Option<(u64, u64)> {
+	Some((
+		crate::mul_div_floor(principal_reserve, lp, lp_supply)?,
+		crate::mul_div_floor(underlying_reserve, lp, lp_supply)?,
+	))
+}
+
+// Output of a swap of `amount_in` against the reserves, after the fee on the input
+pub fn swap_out(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_bps: u16) -> Option<u64> {
+	let amount_in_after_fee =
+		crate::mul_div_floor(amount_in, FEE_DENOMINATOR - fee_bps as u64, FEE_DENOMINATOR)?;
+
+	crate::mul_div_floor(
+		reserve_out,
+		amount_in_after_fee,
+		reserve_in.checked_add(amount_in_after_fee)?,
+	)
+}
+
+// Integer square root, rounded down
+fn isqrt(n: u128) -> u128 {
+	if n < 2 {
+		return n;
+	}
+
+	let mut x = n;
+	let mut y = n.div_ceil(2);
+	while y < x {
+		x = y;
+		y = (x + n / x) / 2;
+	}
+
+	x
+}
+
+#[cfg(test)]
+mod tests {
+	use {super::*, proptest::prelude::*};
+
+	#[test]
+	fn isqrt_rounds_down() {
+		for (n, root) in [(0, 0), (1, 1), (2, 1), (3, 1), (4, 2), (15, 3), (16, 4)] {
+			assert_eq!(isqrt(n), root);
+		}
+		assert_eq!(isqrt(u64::MAX as u128 * u64::MAX as u128), u64::MAX as u128);
+		assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+	}
+
+	#[test]
+	fn first_deposit_mints_the_geometric_mean() {
+		assert_eq!(deposit_amounts(400, 100, 0, 0, 0), Some((200, 400, 100)));
+	}
+
+	#[test]
+	fn later_deposits_take_the_reserve_ratio() {
+		// Limited by the principal, only half of the underlying is taken
+		assert_eq!(
+			deposit_amounts(100, 100, 1_000, 500, 700),
+			Some((70, 100, 50))
+		);
+		// The other side is rounded up in favour of the pool
+		assert_eq!(
+			deposit_amounts(10, 10, 1_000, 333, 1_000),
+			Some((10, 10, 4))
+		);
+		assert_eq!(amounts_for_withdrawal(70, 1_100, 550, 770), Some((100, 50)));
+	}
+
+	#[test]
+	fn swap_out_takes_the_fee_and_rounds_down() {
+		assert_eq!(swap_out(100, 1_000, 1_000, 0), Some(90));
+		assert_eq!(swap_out(100, 1_000, 1_000, MAX_POOL_FEE_BPS), Some(82));
+		assert_eq!(swap_out(0, 1_000, 1_000, 0), Some(0));
+	}
+
+	proptest! {
+		#[test]
+		fn swap_never_lowers_the_product(
+			amount_in in 0..1_000_000_000u64,
+			reserve_in in 1..1_000_000_000u64,
+			reserve_out in 1..1_000_000_000u64,
+			fee_bps in 0..=MAX_POOL_FEE_BPS,
+		) {
+			let out = swap_out(amount_in, reserve_in, reserve_out, fee_bps).unwrap();
+
+			prop_assert!(out < reserve_out);
+			prop_assert!(
+				(reserve_in + amount_in) as u128 * (reserve_out - out) as u128
+					>= reserve_in as u128 * reserve_out as u128
+			);
+		}
+	}
+}
@@ -1,20 +1,33 @@
 use {
 	crate::{
+		derive_tokenizer,
 		error::TokenizerError,
-		get_principal_mint_address, get_tokenizer_address, get_yield_mint_address,
-		instruction::TokenizerInstruction,
-		state::{TokenizerState, STATE_SIZE},
-		Expiry,
+		exchange_rate, get_lp_mint_address, get_pool_address, get_principal_mint_address,
+		get_program_config_address, get_user_position_address, get_yield_mint_address,
+		instruction::{RedeemOrder, TokenizerInstruction},
+		mul_div_floor,
+		pool::{
+			amounts_for_withdrawal, deposit_amounts, swap_out, PoolState, MAX_POOL_FEE_BPS,
+			POOL_SIZE,
+		},
+		simulate_claim_yield, simulate_redeem_principal,
+		spl_utils::{assert_mint_authority, load_mint, load_token_account},
+		state::{
+			ProgramConfig, TokenizerState, UserPosition, CONFIG_SIZE, STATE_SIZE,
+			USER_POSITION_SIZE,
+		},
+		validate_fixed_apy, Expiry, BPS_DENOMINATOR, MAX_BATCH_REDEEM_ENTRIES, RATE_SCALE,
 	},
 	borsh::{BorshDeserialize, BorshSerialize},
 	solana_program::{
 		account_info::{next_account_info, AccountInfo},
+		bpf_loader_upgradeable::{self, UpgradeableLoaderState},
 		clock,
 		entrypoint::ProgramResult,
 		msg,
-		program::{invoke, invoke_signed},
+		program::{invoke, invoke_signed, set_return_data},
 		program_error::ProgramError,
-		program_pack::Pack,
+		program_option::COption,
 		pubkey::Pubkey,
 		system_instruction, system_program,
 		sysvar::{clock::Clock, rent, Sysvar},
@@ -26,6 +39,7 @@ const MINT_SIZE: usize = 82;
 pub enum RedemptionMode {
 	Mature,
 	PrincipalYield,
+	Unwind,
 }
 
 pub struct TokenizerProcessor;
@@ -40,12 +54,30 @@ impl TokenizerProcessor {
 			TokenizerInstruction::try_from_slice(data)
 				.map_err(|_| ProgramError::InvalidInstructionData)?;
 
+		// A frozen deployment only lets existing positions wind down
+		#[cfg(feature = "frozen")]
+		if matches!(
+			instruction,
+			TokenizerInstruction::InitializeTokenizer { .. }
+				| TokenizerInstruction::InitializeMints { .. }
+				| TokenizerInstruction::InitializeTokenizerAndMints { .. }
+				| TokenizerInstruction::DepositUnderlying { .. }
+				| TokenizerInstruction::TokenizePrincipal { .. }
+				| TokenizerInstruction::TokenizeYield { .. }
+				| TokenizerInstruction::DepositAndTokenize { .. }
+				| TokenizerInstruction::InitializeAmm { .. }
+				| TokenizerInstruction::AddLiquidity { .. }
+		) {
+			return Err(TokenizerError::ProgramFrozen.into());
+		}
+
 		match instruction {
 			TokenizerInstruction::InitializeTokenizer {
 				underlying_mint,
 				principal_token_mint,
 				yield_token_mint,
 				expiry,
+				expiry_date,
 				fixed_apy,
 			} => Self::process_initialize_lysergic_tokenizer(
 				accounts,
@@ -53,17 +85,20 @@ impl TokenizerProcessor {
 				principal_token_mint,
 				yield_token_mint,
 				&expiry,
+				expiry_date,
 				fixed_apy,
 			),
 			TokenizerInstruction::InitializeMints {
 				underlying_mint,
-				expiry,
-			} => Self::process_initialize_mints(accounts, underlying_mint, &expiry),
+				expiry: _,
+				expiry_date,
+			} => Self::process_initialize_mints(accounts, underlying_mint, expiry_date),
 			TokenizerInstruction::InitializeTokenizerAndMints {
 				underlying_mint,
 				principal_token_mint,
 				yield_token_mint,
 				expiry,
+				expiry_date,
 				fixed_apy,
 			} => Self::process_initialize_tokenizer_and_mints(
 				accounts,
@@ -71,6 +106,7 @@ impl TokenizerProcessor {
 				principal_token_mint,
 				yield_token_mint,
 				expiry,
+				expiry_date,
 				fixed_apy,
 			),
 			TokenizerInstruction::DepositUnderlying { amount } => {
@@ -85,20 +121,99 @@ impl TokenizerProcessor {
 			TokenizerInstruction::DepositAndTokenize { amount } => {
 				Self::process_deposit_and_tokenize(accounts, amount)
 			}
-			TokenizerInstruction::RedeemPrincipalAndYield { amount } => {
-				Self::process_redeem_principal_and_yield(accounts, amount)
-			}
-			TokenizerInstruction::RedeemMaturePrincipal { principal_amount } => {
-				Self::process_redeem_mature_principal(accounts, principal_amount)
-			}
-			TokenizerInstruction::ClaimYield { yield_amount } => {
-				Self::process_claim_yield(accounts, yield_amount)
+			TokenizerInstruction::RedeemPrincipalAndYield {
+				amount,
+				min_total_underlying_out,
+				unwrap,
+				redeem_order,
+			} => Self::process_redeem_principal_and_yield(
+				accounts,
+				RedemptionMode::PrincipalYield,
+				amount,
+				min_total_underlying_out,
+				unwrap,
+				redeem_order,
+			),
+			TokenizerInstruction::RedeemMaturePrincipal {
+				principal_amount,
+				unwrap,
+				close_principal_ata,
+			} => Self::process_redeem_mature_principal(
+				accounts,
+				principal_amount,
+				unwrap,
+				close_principal_ata,
+			),
+			TokenizerInstruction::ClaimYield {
+				yield_amount,
+				strict,
+				unwrap,
+			} => {
+				Self::process_claim_yield(accounts, yield_amount, strict, false, unwrap).map(|_| ())
 			}
 			TokenizerInstruction::Terminate => Self::process_terminate(accounts),
 			TokenizerInstruction::TerminateTokenizer => {
 				Self::process_terminate_lysergic_tokenizer(accounts)
 			}
 			TokenizerInstruction::TerminateMints => Self::process_terminate_mints(accounts),
+			TokenizerInstruction::GetExchangeRate => Self::process_get_exchange_rate(accounts),
+			TokenizerInstruction::RecreateVault => Self::process_recreate_vault(accounts),
+			TokenizerInstruction::UpdateFixedApy { fixed_apy } => {
+				Self::process_update_fixed_apy(accounts, fixed_apy)
+			}
+			TokenizerInstruction::BatchRedeem { entries } => {
+				Self::process_batch_redeem(accounts, &entries)
+			}
+			TokenizerInstruction::TerminatePrincipalMint => {
+				Self::process_terminate_principal_mint(accounts)
+			}
+			TokenizerInstruction::InitConfig {
+				min_tenor,
+				max_tenor,
+			} => Self::process_init_config(accounts, min_tenor, max_tenor),
+			TokenizerInstruction::UpdateConfig {
+				min_tenor,
+				max_tenor,
+			} => Self::process_update_config(accounts, min_tenor, max_tenor),
+			TokenizerInstruction::UpdateMinHold { min_hold_seconds } => {
+				Self::process_update_min_hold(accounts, min_hold_seconds)
+			}
+			TokenizerInstruction::Reconcile => Self::process_reconcile(accounts),
+			TokenizerInstruction::UpdateRebasing { rebasing } => {
+				Self::process_update_rebasing(accounts, rebasing)
+			}
+			TokenizerInstruction::Unwind {
+				fraction_bps,
+				min_total_underlying_out,
+			} => Self::process_unwind(accounts, fraction_bps, min_total_underlying_out),
+			TokenizerInstruction::InitializeAmm { fee_bps } => {
+				Self::process_initialize_amm(accounts, fee_bps)
+			}
+			TokenizerInstruction::AddLiquidity {
+				principal_amount,
+				underlying_amount,
+				min_lp_out,
+			} => Self::process_add_liquidity(
+				accounts,
+				principal_amount,
+				underlying_amount,
+				min_lp_out,
+			),
+			TokenizerInstruction::RemoveLiquidity {
+				lp_amount,
+				min_principal_out,
+				min_underlying_out,
+			} => Self::process_remove_liquidity(
+				accounts,
+				lp_amount,
+				min_principal_out,
+				min_underlying_out,
+			),
+			TokenizerInstruction::Swap {
+				principal_in,
+				amount_in,
+				min_amount_out,
+			} => Self::process_swap(accounts, principal_in, amount_in, min_amount_out),
 		}
 	}
 
@@ -108,6 +223,7 @@ impl TokenizerProcessor {
 		principal_token_mint: Pubkey,
 		yield_token_mint: Pubkey,
 		expiry: &Expiry,
+		expiry_date: i64,
 		fixed_apy: u64,
 	) -> ProgramResult {
 		let account_info_iter = &mut accounts.iter();
@@ -118,16 +234,16 @@ impl TokenizerProcessor {
 		let token_program = next_account_info(account_info_iter)?;
 		let system_program = next_account_info(account_info_iter)?;
 		let atoken_program = next_account_info(account_info_iter)?;
+		let program_config_account = next_account_info(account_info_iter)?;
 
 		let rent = rent::Rent::get()?;
 		let timestamp = Clock::get()?.unix_timestamp;
 
-		let expiry_date = expiry
-			.to_expiry_date(timestamp)
-			.expect("Invalid expiry date");
+		if !expiry.is_valid_expiry_date(timestamp, expiry_date) {
+			return Err(TokenizerError::InvalidExpiryDate.into());
+		}
 
-		let (tokenizer_key, bump) =
-			get_tokenizer_address(&underlying_mint_account.key, expiry_date);
+		let (tokenizer_key, bump) = derive_tokenizer(underlying_mint_account.key, expiry_date)?;
 		msg!("Tokenizer key: {:?}", tokenizer_key);
 		let (principal_mint, _) = get_principal_mint_address(&tokenizer_key);
 		let (yield_mint, _) = get_yield_mint_address(&tokenizer_key);
@@ -144,7 +260,7 @@ impl TokenizerProcessor {
 		// Check if the underlying vault account address is correct
 		if underlying_vault_account.key
 			!= &spl_associated_token_account::get_associated_token_address(
-				&lysergic_tokenizer_account.key,
+				lysergic_tokenizer_account.key,
 				&underlying_mint,
 			) {
 			return Err(TokenizerError::IncorrectVaultAddress.into());
@@ -156,16 +272,17 @@ impl TokenizerProcessor {
 		}
 
 		// Check principal token mint address
-		if &principal_token_mint != &principal_mint {
+		if principal_token_mint != principal_mint {
 			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
 		}
 
 		// Check yield token mint address
-		if &yield_token_mint != &yield_mint {
+		if yield_token_mint != yield_mint {
 			return Err(TokenizerError::IncorrectYieldMintAddress.into());
 		}
 
-		// Check token program
+		// Check token program, only classic SPL Token is supported for now; later
+		// instructions are checked against the program recorded in the state
 		if token_program.key != &spl_token::id() {
 			return Err(ProgramError::IncorrectProgramId);
 		}
@@ -179,6 +296,22 @@ impl TokenizerProcessor {
 			return Err(ProgramError::IncorrectProgramId);
 		}
 
+		// Check the tenor is enabled in the program config
+		if program_config_account.key != &get_program_config_address().0
+			|| program_config_account.owner != &crate::id()
+		{
+			return Err(TokenizerError::ConfigNotInitialized.into());
+		}
+
+		let program_config =
+			ProgramConfig::unpack_initialized(&program_config_account.data.borrow()[..])?;
+
+		if !program_config.allows_tenor(expiry.to_months()) {
+			return Err(TokenizerError::TenorNotEnabled.into());
+		}
+
+		validate_fixed_apy(fixed_apy, expiry_date, timestamp)?;
+
 		// Check if the lysergic tokenizer account is already initialized
 		if lysergic_tokenizer_account.owner != &crate::id() {
 			let size = STATE_SIZE;
@@ -237,14 +370,24 @@ impl TokenizerProcessor {
 			)?;
 
 			let lysergic_tokenizer_state = TokenizerState {
+				is_initialized: true,
 				bump,
 				authority: *authority.key,
 				principal_token_mint,
 				yield_token_mint,
 				underlying_mint,
 				underlying_vault: *underlying_vault_account.key,
+				token_program: *token_program.key,
 				expiry_date,
 				fixed_apy,
+				total_deposited: 0,
+				mints_terminated: false,
+				min_hold_seconds: 0,
+				mints_initialized: false,
+				rebasing: false,
+				principal_mint_terminated: false,
+				untokenized_principal: 0,
+				untokenized_yield: 0,
 			};
 
 			lysergic_tokenizer_state
@@ -253,14 +396,14 @@ impl TokenizerProcessor {
 
 			Ok(())
 		} else {
-			return Err(TokenizerError::TokenizerAlreadyInitialized.into());
+			Err(TokenizerError::TokenizerAlreadyInitialized.into())
 		}
 	}
 
 	fn process_initialize_mints(
 		accounts: &[AccountInfo],
 		underlying_mint: Pubkey,
-		expiry: &Expiry,
+		expiry_date: i64,
 	) -> ProgramResult {
 		let account_info_iter = &mut accounts.iter();
 		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
@@ -272,14 +415,10 @@ impl TokenizerProcessor {
 		let system_program = next_account_info(account_info_iter)?;
 
 		let rent = rent::Rent::get()?;
-		let timestamp = Clock::get()?.unix_timestamp;
-
-		let expiry_date = match expiry.to_expiry_date(timestamp) {
-			Some(expiry_date) => expiry_date,
-			None => return Err(TokenizerError::InvalidExpiryDate.into()),
-		};
 
-		let (tokenizer_key, bump) = get_tokenizer_address(&underlying_mint, expiry_date);
+		// The expiry date was checked against the clock when the tokenizer was initialized and
+		// is compared with the stored one below, so the mints can be created on any later day
+		let (tokenizer_key, bump) = derive_tokenizer(&underlying_mint, expiry_date)?;
 		let (principal_mint, pbump) = get_principal_mint_address(&tokenizer_key);
 		let (yield_mint, ybump) = get_yield_mint_address(&tokenizer_key);
 
@@ -295,45 +434,63 @@ impl TokenizerProcessor {
 			return Err(ProgramError::IncorrectProgramId);
 		}
 
-		// Run different safety checks if the lysergic tokenizer account is initialized or
-		// unintialized
-		if lysergic_tokenizer_account.owner == &crate::id() {
-			let lysergic_tokenizer_state = match TokenizerState::try_from_slice(
-				&lysergic_tokenizer_account.data.borrow(),
+		// The tokenizer must exist first so it can record that its mints are ready for deposits
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if &lysergic_tokenizer_state.principal_token_mint != principal_token_mint_account.key {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
+		}
+
+		if &lysergic_tokenizer_state.yield_token_mint != yield_token_mint_account.key {
+			return Err(TokenizerError::IncorrectYieldMintAddress.into());
+		}
+
+		if &lysergic_tokenizer_state.underlying_mint != underlying_mint_account.key {
+			return Err(TokenizerError::IncorrectUnderlyingMintAddress.into());
+		}
+
+		if lysergic_tokenizer_state.expiry_date != expiry_date {
+			return Err(TokenizerError::InvalidExpiryDate.into());
+		}
+
+		if &lysergic_tokenizer_state.token_program != token_program.key {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if lysergic_tokenizer_state.underlying_vault
+			!= spl_associated_token_account::get_associated_token_address(
+				lysergic_tokenizer_account.key,
+				&lysergic_tokenizer_state.underlying_mint,
 			) {
-				Ok(data) => data,
-				Err(_) => return Err(ProgramError::InvalidAccountData),
-			};
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
 
-			if &lysergic_tokenizer_state.principal_token_mint != principal_token_mint_account.key {
-				return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
-			}
+		if underlying_mint_account.key != &underlying_mint {
+			return Err(TokenizerError::IncorrectUnderlyingMintAddress.into());
+		}
 
-			if &lysergic_tokenizer_state.yield_token_mint != yield_token_mint_account.key {
-				return Err(TokenizerError::IncorrectYieldMintAddress.into());
-			}
+		// Principal and yield are redeemable 1:1 for the underlying, so they share its decimals
+		let decimals = load_mint(underlying_mint_account)?.decimals;
 
-			if &lysergic_tokenizer_state.underlying_mint != underlying_mint_account.key {
-				return Err(TokenizerError::IncorrectUnderlyingMintAddress.into());
-			}
+		// The mints must always be the PDAs derived from the tokenizer, not just match the state
+		if principal_token_mint_account.key != &principal_mint {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
+		}
 
-			if lysergic_tokenizer_state.expiry_date != expiry_date {
-				return Err(TokenizerError::InvalidExpiryDate.into());
-			}
+		if yield_token_mint_account.key != &yield_mint {
+			return Err(TokenizerError::IncorrectYieldMintAddress.into());
+		}
 
-			if lysergic_tokenizer_state.underlying_vault
-				!= spl_associated_token_account::get_associated_token_address(
-					lysergic_tokenizer_account.key,
-					&lysergic_tokenizer_state.underlying_mint,
-				) {
-				return Err(TokenizerError::IncorrectVaultAddress.into());
-			}
-		} else if lysergic_tokenizer_account.owner != &crate::id() {
-			if principal_token_mint_account.key != &principal_mint {
-				return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
-			}
-			if yield_token_mint_account.key != &yield_mint {
-				return Err(TokenizerError::IncorrectYieldMintAddress.into());
+		// The mints are created here, so they must not already hold data or belong to a program
+		for mint_account in [principal_token_mint_account, yield_token_mint_account] {
+			if mint_account.owner != &system_program::id() || !mint_account.data_is_empty() {
+				msg!("Mint account {} is already in use", mint_account.key);
+				return Err(ProgramError::AccountAlreadyInitialized);
 			}
 		}
 
@@ -350,10 +507,10 @@ impl TokenizerProcessor {
 		invoke_signed(
 			&system_instruction::create_account(
 				authority.key,
-				&principal_token_mint_account.key,
+				principal_token_mint_account.key,
 				required_lamports_principal,
 				MINT_SIZE as u64,
-				&spl_token::id(),
+				token_program.key,
 			),
 			&[
 				authority.clone(),
@@ -371,10 +528,10 @@ impl TokenizerProcessor {
 		invoke_signed(
 			&system_instruction::create_account(
 				authority.key,
-				&yield_token_mint_account.key,
+				yield_token_mint_account.key,
 				required_lamports_yield,
 				MINT_SIZE as u64,
-				&spl_token::id(),
+				token_program.key,
 			),
 			&[
 				authority.clone(),
@@ -396,7 +553,7 @@ impl TokenizerProcessor {
 				principal_token_mint_account.key,
 				lysergic_tokenizer_account.key,
 				None,
-				6,
+				decimals,
 			)?,
 			&[principal_token_mint_account.clone(), token_program.clone()],
 			&[&[
@@ -415,7 +572,7 @@ impl TokenizerProcessor {
 				yield_token_mint_account.key,
 				lysergic_tokenizer_account.key,
 				None,
-				6,
+				decimals,
 			)?,
 			&[yield_token_mint_account.clone(), token_program.clone()],
 			&[&[
@@ -426,6 +583,10 @@ impl TokenizerProcessor {
 			]],
 		)?;
 
+		lysergic_tokenizer_state.mints_initialized = true;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
+
 		Ok(())
 	}
 
@@ -435,6 +596,7 @@ impl TokenizerProcessor {
 		principal_token_mint: Pubkey,
 		yield_token_mint: Pubkey,
 		expiry: Expiry,
+		expiry_date: i64,
 		fixed_apy: u64,
 	) -> ProgramResult {
 		let account_info_iter = &mut accounts.iter();
@@ -448,6 +610,7 @@ impl TokenizerProcessor {
 		let token_program = next_account_info(account_info_iter)?;
 		let system_program = next_account_info(account_info_iter)?;
 		let atoken_program = next_account_info(account_info_iter)?;
+		let program_config_account = next_account_info(account_info_iter)?;
 
 		let initialize_tokenizer_accounts = [
 			lysergic_tokenizer_account.clone(),
@@ -457,6 +620,7 @@ impl TokenizerProcessor {
 			token_program.clone(),
 			system_program.clone(),
 			atoken_program.clone(),
+			program_config_account.clone(),
 		];
 
 		let initialize_mint_accounts = [
@@ -475,10 +639,11 @@ impl TokenizerProcessor {
 			principal_token_mint,
 			yield_token_mint,
 			&expiry,
+			expiry_date,
 			fixed_apy,
 		)?;
 
-		Self::process_initialize_mints(&initialize_mint_accounts, underlying_mint, &expiry)?;
+		Self::process_initialize_mints(&initialize_mint_accounts, underlying_mint, expiry_date)?;
 
 		Ok(())
 	}
@@ -490,10 +655,12 @@ impl TokenizerProcessor {
 		let user_account = next_account_info(account_info_iter)?;
 		let user_underlying_token_account = next_account_info(account_info_iter)?;
 		let token_program = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+		let user_position_account = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
 
-		let amount = spl_token::ui_amount_to_amount(amount as f64, 6);
-
-		let lysergic_tokenizer_state = TokenizerState::try_from_slice(
+		let mut lysergic_tokenizer_state = TokenizerState::unpack_initialized(
 			&lysergic_tokenizer_account.data.borrow()[..STATE_SIZE],
 		)?;
 
@@ -502,7 +669,16 @@ impl TokenizerProcessor {
 			return Err(TokenizerError::TokenizerNotInitialized.into());
 		}
 
-		if underlying_vault_account.owner != &spl_token::id() {
+		// Underlying deposited before the mints exist could not be tokenized
+		if !lysergic_tokenizer_state.mints_initialized {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if lysergic_tokenizer_state.expiry_date < clock::Clock::get()?.unix_timestamp {
+			return Err(TokenizerError::ExpiryDateElapsed.into());
+		}
+
+		if underlying_vault_account.owner != &lysergic_tokenizer_state.token_program {
 			return Err(TokenizerError::IncorrectVaultAddress.into());
 		}
 
@@ -510,6 +686,17 @@ impl TokenizerProcessor {
 			return Err(TokenizerError::IncorrectVaultAddress.into());
 		}
 
+		// Check the vault's token owner is the tokenizer, not just its address
+		let underlying_vault_data = load_token_account(underlying_vault_account)?;
+
+		if &underlying_vault_data.owner != lysergic_tokenizer_account.key {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if underlying_vault_data.mint != lysergic_tokenizer_state.underlying_mint {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
 		if !user_account.is_signer {
 			return Err(ProgramError::MissingRequiredSignature);
 		}
@@ -522,28 +709,55 @@ impl TokenizerProcessor {
 			return Err(TokenizerError::InvalidUserAccount.into());
 		}
 
-		if token_program.key != &spl_token::id() {
+		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
+		}
+
+		if yield_token_mint_account.key != &lysergic_tokenizer_state.yield_token_mint {
+			return Err(TokenizerError::IncorrectYieldMintAddress.into());
+		}
+
+		if token_program.key != &lysergic_tokenizer_state.token_program {
 			return Err(ProgramError::IncorrectProgramId);
 		}
 
-		msg!("Depositing underlying...");
-		// Transfer underlying token from user to lysergic tokenizer
-		invoke(
-			&spl_token::instruction::transfer(
-				token_program.key,
-				user_underlying_token_account.key,
-				underlying_vault_account.key,
-				user_account.key,
-				&[],
-				amount,
-			)?,
-			&[
-				user_underlying_token_account.clone(),
-				underlying_vault_account.clone(),
-				user_account.clone(),
-				token_program.clone(),
-			],
+		Self::deposit(
+			&mut lysergic_tokenizer_state,
+			underlying_vault_account,
+			user_account,
+			user_underlying_token_account,
+			token_program,
+			amount,
+		)?;
+
+		// Credit the deposit to the user, who can then tokenize up to it once into principal
+		// and once into yield
+		let mut user_position = Self::load_user_position(
+			lysergic_tokenizer_account,
+			user_account,
+			user_position_account,
+			system_program,
 		)?;
+		user_position.record_deposit(amount)?;
+		user_position.serialize(&mut &mut user_position_account.data.borrow_mut()[..])?;
+
+		lysergic_tokenizer_state.untokenized_principal = lysergic_tokenizer_state
+			.untokenized_principal
+			.checked_add(amount)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+		lysergic_tokenizer_state.untokenized_yield = lysergic_tokenizer_state
+			.untokenized_yield
+			.checked_add(amount)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
+		Self::assert_vault_solvency(
+			&lysergic_tokenizer_state,
+			underlying_vault_account,
+			principal_token_mint_account,
+			yield_token_mint_account,
+		)?;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
 
 		Ok(())
 	}
@@ -555,25 +769,39 @@ impl TokenizerProcessor {
 		let user_account = next_account_info(account_info_iter)?;
 		let user_principal_token_account = next_account_info(account_info_iter)?;
 		let token_program = next_account_info(account_info_iter)?;
-
-		let amount = spl_token::ui_amount_to_amount(amount as f64, 6);
+		let system_program = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+		let user_position_account = next_account_info(account_info_iter)?;
+		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
 
 		if lysergic_tokenizer_account.owner != &crate::id() {
 			return Err(TokenizerError::TokenizerNotInitialized.into());
 		}
 
-		let lysergic_tokenizer_state =
-			TokenizerState::try_from_slice(&lysergic_tokenizer_account.data.borrow()[..])?;
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
 
-		// Check to see if the expiry date has elapsed
-		if lysergic_tokenizer_state.expiry_date < clock::Clock::get()?.unix_timestamp {
-			return Err(TokenizerError::ExpiryDateElapsed.into());
-		}
+		// Only underlying already deposited, before expiry, is tokenized, so unlike deposits
+		// this is allowed after expiry
+		let timestamp = clock::Clock::get()?.unix_timestamp;
 
 		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
 			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
 		}
 
+		if yield_token_mint_account.key != &lysergic_tokenizer_state.yield_token_mint {
+			return Err(TokenizerError::IncorrectYieldMintAddress.into());
+		}
+
+		if underlying_vault_account.key != &lysergic_tokenizer_state.underlying_vault {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if !user_account.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
 		if user_principal_token_account.key
 			!= &spl_associated_token_account::get_associated_token_address(
 				user_account.key,
@@ -582,67 +810,58 @@ impl TokenizerProcessor {
 			return Err(TokenizerError::InvalidUserAccount.into());
 		}
 
-		if token_program.key != &spl_token::id() {
+		if token_program.key != &lysergic_tokenizer_state.token_program {
 			return Err(ProgramError::IncorrectProgramId);
 		}
 
+		let mut user_position = Self::load_user_position(
+			lysergic_tokenizer_account,
+			user_account,
+			user_position_account,
+			system_program,
+		)?;
+		user_position.take_untokenized_principal(amount)?;
+		lysergic_tokenizer_state.untokenized_principal = lysergic_tokenizer_state
+			.untokenized_principal
+			.checked_sub(amount)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
 		// We may want to create a principal token account for the user if it doesn't exist
 		if user_principal_token_account.owner != token_program.key {
 			msg!("No user principal account found, creating...");
-			let system_program = next_account_info(account_info_iter)?;
-			let atoken_program = next_account_info(account_info_iter)?;
-
-			if system_program.key != &system_program::id() {
-				return Err(ProgramError::IncorrectProgramId);
-			}
-
-			if atoken_program.key != &spl_associated_token_account::id() {
-				return Err(ProgramError::IncorrectProgramId);
-			}
-
-			invoke(
-				&spl_associated_token_account::instruction::create_associated_token_account(
-					user_account.key,
-					user_account.key,
-					&lysergic_tokenizer_state.principal_token_mint,
-					token_program.key,
-				),
-				&[
-					user_account.clone(),
-					user_principal_token_account.clone(),
-					user_account.clone(),
-					principal_token_mint_account.clone(),
-					system_program.clone(),
-					token_program.clone(),
-					atoken_program.clone(),
-				],
+			Self::create_user_token_account(
+				principal_token_mint_account,
+				user_account,
+				user_principal_token_account,
+				token_program,
+				system_program,
+				atoken_program,
 			)?;
 		}
 
-		msg!("Minting principal to user...");
-		// Mint principal token to user
-		invoke_signed(
-			&spl_token::instruction::mint_to(
-				token_program.key,
-				principal_token_mint_account.key,
-				user_principal_token_account.key,
-				lysergic_tokenizer_account.key,
-				&[],
-				amount,
-			)?,
-			&[
-				principal_token_mint_account.clone(),
-				user_principal_token_account.clone(),
-				lysergic_tokenizer_account.clone(),
-				token_program.clone(),
-			],
-			&[&[
-				b"tokenizer",
-				&lysergic_tokenizer_state.underlying_mint.to_bytes()[..],
-				&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
-				&[lysergic_tokenizer_state.bump],
-			]],
+		// Principal is minted 1:1 with the underlying it is redeemed for at maturity, the fixed
+		// APY only sets the price it trades at before then, see `exchange_rate`
+		msg!("Minting {} principal to user...", amount);
+		Self::mint_to_user(
+			&lysergic_tokenizer_state,
+			lysergic_tokenizer_account,
+			principal_token_mint_account,
+			user_principal_token_account,
+			token_program,
+			amount,
+		)?;
+
+		user_position.record_principal(amount, timestamp)?;
+		user_position.serialize(&mut &mut user_position_account.data.borrow_mut()[..])?;
+
+		Self::assert_vault_solvency(
+			&lysergic_tokenizer_state,
+			underlying_vault_account,
+			principal_token_mint_account,
+			yield_token_mint_account,
 		)?;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
 
 		Ok(())
 	}
@@ -654,24 +873,36 @@ impl TokenizerProcessor {
 		let user_account = next_account_info(account_info_iter)?;
 		let user_yield_token_account = next_account_info(account_info_iter)?;
 		let token_program = next_account_info(account_info_iter)?;
-
-		let amount = spl_token::ui_amount_to_amount(amount as f64, 6);
+		let system_program = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+		let user_position_account = next_account_info(account_info_iter)?;
+		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
 
 		if lysergic_tokenizer_account.owner != &crate::id() {
 			return Err(TokenizerError::TokenizerNotInitialized.into());
 		}
 
-		let lysergic_tokenizer_state =
-			TokenizerState::try_from_slice(&lysergic_tokenizer_account.data.borrow()[..])?;
-
-		if lysergic_tokenizer_state.expiry_date < clock::Clock::get()?.unix_timestamp {
-			return Err(TokenizerError::ExpiryDateElapsed.into());
-		}
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
 
+		// Like principal, yield can be tokenized after expiry from an earlier deposit
 		if yield_token_mint_account.key != &lysergic_tokenizer_state.yield_token_mint {
 			return Err(TokenizerError::IncorrectYieldMintAddress.into());
 		}
 
+		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
+		}
+
+		if underlying_vault_account.key != &lysergic_tokenizer_state.underlying_vault {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if !user_account.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
 		if user_yield_token_account.key
 			!= &spl_associated_token_account::get_associated_token_address(
 				user_account.key,
@@ -680,71 +911,62 @@ impl TokenizerProcessor {
 			return Err(TokenizerError::InvalidUserAccount.into());
 		}
 
-		if token_program.key != &spl_token::id() {
+		if token_program.key != &lysergic_tokenizer_state.token_program {
 			return Err(ProgramError::IncorrectProgramId);
 		}
 
+		let mut user_position = Self::load_user_position(
+			lysergic_tokenizer_account,
+			user_account,
+			user_position_account,
+			system_program,
+		)?;
+		user_position.take_untokenized_yield(amount)?;
+		lysergic_tokenizer_state.untokenized_yield = lysergic_tokenizer_state
+			.untokenized_yield
+			.checked_sub(amount)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
 		// We may want to create a yield token account for the user if it doesn't exist
 		if user_yield_token_account.owner != token_program.key {
 			msg!("No user yield account found, creating...");
-			let system_program = next_account_info(account_info_iter)?;
-			let atoken_program = next_account_info(account_info_iter)?;
-
-			if system_program.key != &system_program::id() {
-				return Err(ProgramError::IncorrectProgramId);
-			}
-
-			if atoken_program.key != &spl_associated_token_account::id() {
-				return Err(ProgramError::IncorrectProgramId);
-			}
-
-			invoke(
-				&spl_associated_token_account::instruction::create_associated_token_account(
-					user_account.key,
-					user_account.key,
-					&lysergic_tokenizer_state.yield_token_mint,
-					token_program.key,
-				),
-				&[
-					user_account.clone(),
-					user_yield_token_account.clone(),
-					user_account.clone(),
-					yield_token_mint_account.clone(),
-					system_program.clone(),
-					token_program.clone(),
-					atoken_program.clone(),
-				],
+			Self::create_user_token_account(
+				yield_token_mint_account,
+				user_account,
+				user_yield_token_account,
+				token_program,
+				system_program,
+				atoken_program,
 			)?;
 		}
 
 		msg!("Minting yield to user...");
-		// Mint yield token to user
-		invoke_signed(
-			&spl_token::instruction::mint_to(
-				token_program.key,
-				yield_token_mint_account.key,
-				user_yield_token_account.key,
-				lysergic_tokenizer_account.key,
-				&[],
-				amount,
-			)?,
-			&[
-				yield_token_mint_account.clone(),
-				user_yield_token_account.clone(),
-				lysergic_tokenizer_account.clone(),
-				token_program.clone(),
-			],
-			&[&[
-				b"tokenizer",
-				&lysergic_tokenizer_state.underlying_mint.to_bytes()[..],
-				&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
-				&[lysergic_tokenizer_state.bump],
-			]],
+		Self::mint_to_user(
+			&lysergic_tokenizer_state,
+			lysergic_tokenizer_account,
+			yield_token_mint_account,
+			user_yield_token_account,
+			token_program,
+			amount,
+		)?;
+
+		user_position.serialize(&mut &mut user_position_account.data.borrow_mut()[..])?;
+
+		Self::assert_vault_solvency(
+			&lysergic_tokenizer_state,
+			underlying_vault_account,
+			principal_token_mint_account,
+			yield_token_mint_account,
 		)?;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
 
 		Ok(())
 	}
 
+	// Performs the deposit and both mints in a single pass, loading the tokenizer state and
+	// validating every account once rather than once per step. The instruction still succeeds
+	// or fails as a whole.
 	fn process_deposit_and_tokenize(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
 		let account_info_iter = &mut accounts.iter();
 		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
@@ -758,124 +980,22 @@ impl TokenizerProcessor {
 		let token_program = next_account_info(account_info_iter)?;
 		let system_program = next_account_info(account_info_iter)?;
 		let atoken_program = next_account_info(account_info_iter)?;
-
-		let deposit_accounts = [
-			lysergic_tokenizer_account.clone(),
-			underlying_vault_account.clone(),
-			user_account.clone(),
-			user_underlying_token_account.clone(),
-			token_program.clone(),
-		];
-
-		let tokenize_principal_accounts = vec![
-			lysergic_tokenizer_account.clone(),
-			principal_token_mint_account.clone(),
-			user_account.clone(),
-			user_principal_token_account.clone(),
-			token_program.clone(),
-			system_program.clone(),
-			atoken_program.clone(),
-		];
-
-		let tokenize_yield_accounts = vec![
-			lysergic_tokenizer_account.clone(),
-			yield_token_mint_account.clone(),
-			user_account.clone(),
-			user_yield_token_account.clone(),
-			token_program.clone(),
-			system_program.clone(),
-			atoken_program.clone(),
-		];
-
-		Self::process_deposit_underlying(&deposit_accounts, amount)?;
-		Self::process_tokenize_principal(&tokenize_principal_accounts, amount)?;
-		Self::process_tokenize_yield(&tokenize_yield_accounts, amount)?;
-
-		Ok(())
-	}
-
-	fn process_redeem_principal_and_yield(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
-		msg!("Redeem principal and yield...");
-		let account_info_iter = &mut accounts.iter();
-		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
-		let underlying_vault_account = next_account_info(account_info_iter)?;
-		let underlying_mint_account = next_account_info(account_info_iter)?;
-		let principal_token_mint_account = next_account_info(account_info_iter)?;
-		let yield_token_mint_account = next_account_info(account_info_iter)?;
-		let user_account = next_account_info(account_info_iter)?;
-		let user_underlying_token_account = next_account_info(account_info_iter)?;
-		let user_principal_token_account = next_account_info(account_info_iter)?;
-		let user_yield_token_account = next_account_info(account_info_iter)?;
-		let token_program = next_account_info(account_info_iter)?;
-
-		let redeem_principal_accounts = [
-			lysergic_tokenizer_account.clone(),
-			underlying_vault_account.clone(),
-			underlying_mint_account.clone(),
-			principal_token_mint_account.clone(),
-			user_account.clone(),
-			user_underlying_token_account.clone(),
-			user_principal_token_account.clone(),
-			token_program.clone(),
-		];
-
-		let claim_yield_accounts = [
-			lysergic_tokenizer_account.clone(),
-			underlying_vault_account.clone(),
-			underlying_mint_account.clone(),
-			yield_token_mint_account.clone(),
-			user_account.clone(),
-			user_underlying_token_account.clone(),
-			user_yield_token_account.clone(),
-			token_program.clone(),
-		];
-
-		Self::process_redeem_principal(
-			&redeem_principal_accounts,
-			RedemptionMode::PrincipalYield,
-			amount,
-		)?;
-		Self::process_claim_yield(&claim_yield_accounts, amount)?;
-
-		Ok(())
-	}
-
-	fn process_redeem_mature_principal(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
-		Self::process_redeem_principal(accounts, RedemptionMode::Mature, amount)
-	}
-
-	fn process_redeem_principal(
-		accounts: &[AccountInfo],
-		redemption_mode: RedemptionMode,
-		amount: u64,
-	) -> ProgramResult {
-		msg!("Redeeming principal...");
-		let account_info_iter = &mut accounts.iter();
-		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
-		let underlying_vault_account = next_account_info(account_info_iter)?;
-		let underlying_mint_account = next_account_info(account_info_iter)?;
-		let principal_token_mint_account = next_account_info(account_info_iter)?;
-		let user_account = next_account_info(account_info_iter)?;
-		let user_underlying_token_account = next_account_info(account_info_iter)?;
-		let user_principal_token_account = next_account_info(account_info_iter)?;
-		let token_program = next_account_info(account_info_iter)?;
-
-		let amount = spl_token::ui_amount_to_amount(amount as f64, 6);
+		let user_position_account = next_account_info(account_info_iter)?;
 
 		if lysergic_tokenizer_account.owner != &crate::id() {
 			return Err(TokenizerError::TokenizerNotInitialized.into());
 		}
 
-		let lysergic_tokenizer_state =
-			TokenizerState::try_from_slice(&lysergic_tokenizer_account.data.borrow()[..])?;
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
 
-		if let RedemptionMode::Mature = redemption_mode {
-			if lysergic_tokenizer_state.expiry_date >= clock::Clock::get()?.unix_timestamp {
-				return Err(TokenizerError::ExpiryDateNotElapsed.into());
-			}
+		let timestamp = clock::Clock::get()?.unix_timestamp;
+
+		if lysergic_tokenizer_state.expiry_date < timestamp {
+			return Err(TokenizerError::ExpiryDateElapsed.into());
 		}
 
-		if underlying_vault_account.owner != &spl_token::id() {
+		if underlying_vault_account.owner != &lysergic_tokenizer_state.token_program {
 			return Err(TokenizerError::IncorrectVaultAddress.into());
 		}
 
@@ -883,14 +1003,25 @@ impl TokenizerProcessor {
 			return Err(TokenizerError::IncorrectVaultAddress.into());
 		}
 
-		if underlying_mint_account.key != &lysergic_tokenizer_state.underlying_mint {
-			return Err(TokenizerError::IncorrectUnderlyingMintAddress.into());
+		// Check the vault's token owner is the tokenizer, not just its address
+		let underlying_vault_data = load_token_account(underlying_vault_account)?;
+
+		if &underlying_vault_data.owner != lysergic_tokenizer_account.key {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if underlying_vault_data.mint != lysergic_tokenizer_state.underlying_mint {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
 		}
 
 		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
 			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
 		}
 
+		if yield_token_mint_account.key != &lysergic_tokenizer_state.yield_token_mint {
+			return Err(TokenizerError::IncorrectYieldMintAddress.into());
+		}
+
 		if !user_account.is_signer {
 			return Err(ProgramError::MissingRequiredSignature);
 		}
@@ -911,357 +1042,618 @@ impl TokenizerProcessor {
 			return Err(TokenizerError::InvalidUserAccount.into());
 		}
 
-		if token_program.key != &spl_token::id() {
+		if user_yield_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				user_account.key,
+				&lysergic_tokenizer_state.yield_token_mint,
+			) {
+			return Err(TokenizerError::InvalidUserAccount.into());
+		}
+
+		if token_program.key != &lysergic_tokenizer_state.token_program {
 			return Err(ProgramError::IncorrectProgramId);
 		}
 
-		// Check if the user has enough principal tokens to redeem
-		let user_principal_token_account_data = spl_token::state::Account::unpack_from_slice(
-			&user_principal_token_account.data.borrow(),
+		Self::deposit(
+			&mut lysergic_tokenizer_state,
+			underlying_vault_account,
+			user_account,
+			user_underlying_token_account,
+			token_program,
+			amount,
 		)?;
 
-		if user_principal_token_account_data.amount < amount {
-			return Err(TokenizerError::InsufficientFunds.into());
+		if user_principal_token_account.owner != token_program.key {
+			msg!("No user principal account found, creating...");
+			Self::create_user_token_account(
+				principal_token_mint_account,
+				user_account,
+				user_principal_token_account,
+				token_program,
+				system_program,
+				atoken_program,
+			)?;
 		}
 
-		// In the rather unlikely event that a user does not have an underlying token account;
-		// create one for them
-		if user_underlying_token_account.owner != token_program.key {
-			let system_program = next_account_info(account_info_iter)?;
-			if system_program.key != &system_program::id() {
-				return Err(ProgramError::IncorrectProgramId);
-			}
+		// Principal is minted 1:1 with the underlying it is redeemed for at maturity, the fixed
+		// APY only sets the price it trades at before then, see `exchange_rate`
+		msg!("Minting {} principal to user...", amount);
+		Self::mint_to_user(
+			&lysergic_tokenizer_state,
+			lysergic_tokenizer_account,
+			principal_token_mint_account,
+			user_principal_token_account,
+			token_program,
+			amount,
+		)?;
 
-			invoke(
-				&spl_associated_token_account::instruction::create_associated_token_account(
-					user_account.key,
-					user_underlying_token_account.key,
-					&lysergic_tokenizer_state.underlying_mint,
-					user_account.key,
-				),
-				&[
-					user_underlying_token_account.clone(),
-					user_account.clone(),
-					underlying_mint_account.clone(),
-					token_program.clone(),
-					system_program.clone(),
-				],
+		if user_yield_token_account.owner != token_program.key {
+			msg!("No user yield account found, creating...");
+			Self::create_user_token_account(
+				yield_token_mint_account,
+				user_account,
+				user_yield_token_account,
+				token_program,
+				system_program,
+				atoken_program,
 			)?;
 		}
 
-		invoke(
-			&spl_token::instruction::burn(
-				token_program.key,
-				user_principal_token_account.key,
-				principal_token_mint_account.key,
-				user_account.key,
-				&[],
-				amount,
-			)?,
-			&[
-				user_principal_token_account.clone(),
-				principal_token_mint_account.clone(),
-				user_account.clone(),
-				token_program.clone(),
-			],
+		msg!("Minting yield to user...");
+		Self::mint_to_user(
+			&lysergic_tokenizer_state,
+			lysergic_tokenizer_account,
+			yield_token_mint_account,
+			user_yield_token_account,
+			token_program,
+			amount,
 		)?;
 
-		invoke_signed(
+		let mut user_position = Self::load_user_position(
+			lysergic_tokenizer_account,
+			user_account,
+			user_position_account,
+			system_program,
+		)?;
+		user_position.record_principal(amount, timestamp)?;
+		user_position.serialize(&mut &mut user_position_account.data.borrow_mut()[..])?;
+
+		Self::assert_vault_solvency(
+			&lysergic_tokenizer_state,
+			underlying_vault_account,
+			principal_token_mint_account,
+			yield_token_mint_account,
+		)?;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	// Transfer underlying token from user to the vault and record the deposit. The caller is
+	// responsible for validating the accounts and persisting the state.
+	fn deposit<'a>(
+		lysergic_tokenizer_state: &mut TokenizerState,
+		underlying_vault_account: &AccountInfo<'a>,
+		user_account: &AccountInfo<'a>,
+		user_underlying_token_account: &AccountInfo<'a>,
+		token_program: &AccountInfo<'a>,
+		amount: u64,
+	) -> ProgramResult {
+		msg!("Depositing underlying...");
+		invoke(
 			&spl_token::instruction::transfer(
 				token_program.key,
-				underlying_vault_account.key,
 				user_underlying_token_account.key,
-				lysergic_tokenizer_account.key,
+				underlying_vault_account.key,
+				user_account.key,
 				&[],
 				amount,
 			)?,
 			&[
-				underlying_vault_account.clone(),
 				user_underlying_token_account.clone(),
-				lysergic_tokenizer_account.clone(),
+				underlying_vault_account.clone(),
+				user_account.clone(),
+				token_program.clone(),
 			],
-			&[&[
-				b"tokenizer",
-				&underlying_mint_account.key.to_bytes()[..],
-				&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
-				&[lysergic_tokenizer_state.bump],
-			]],
 		)?;
 
+		lysergic_tokenizer_state.total_deposited = lysergic_tokenizer_state
+			.total_deposited
+			.checked_add(amount)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
 		Ok(())
 	}
 
-	fn process_claim_yield(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
-		msg!("Claiming yield...");
-		let account_info_iter = &mut accounts.iter();
-		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
-		let underlying_vault_account = next_account_info(account_info_iter)?;
-		let underlying_mint_account = next_account_info(account_info_iter)?;
-		let yield_token_mint_account = next_account_info(account_info_iter)?;
-		let user_account = next_account_info(account_info_iter)?;
-		let user_underlying_token_account = next_account_info(account_info_iter)?;
-		let user_yield_token_account = next_account_info(account_info_iter)?;
-		let token_program = next_account_info(account_info_iter)?;
-
-		let amount = spl_token::ui_amount_to_amount(amount as f64, 6);
+	// Defence in depth, run after every handler that moves underlying in or out of the vault.
+	// The vault must hold enough to redeem the outstanding principal and pay the yield that is
+	// claimable on top of it; if it does not, an accounting bug has let underlying out and the
+	// instruction is failed rather than draining the vault further. A rebasing vault that lost
+	// value only owes principal what it holds, as principal is then redeemed pro rata.
+	fn assert_solvency(
+		lysergic_tokenizer_state: &TokenizerState,
+		vault_balance: u64,
+		principal_supply: u64,
+		yield_claimable: u64,
+	) -> ProgramResult {
+		let principal_owed = if lysergic_tokenizer_state.rebasing {
+			principal_supply.min(vault_balance)
+		} else {
+			principal_supply
+		};
+		let owed = principal_owed
+			.checked_add(yield_claimable)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
 
-		if lysergic_tokenizer_account.owner != &crate::id() {
-			return Err(TokenizerError::TokenizerNotInitialized.into());
+		if vault_balance < owed {
+			msg!(
+				"Vault holds {} but {} principal and {} yield are owed",
+				vault_balance,
+				principal_owed,
+				yield_claimable
+			);
+			return Err(TokenizerError::VaultInsolvent.into());
 		}
 
-		let lysergic_tokenizer_state =
-			TokenizerState::try_from_slice(&lysergic_tokenizer_account.data.borrow()[..])?;
+		Ok(())
+	}
 
-		if underlying_vault_account.owner != &spl_token::id() {
-			return Err(TokenizerError::IncorrectVaultAddress.into());
-		}
+	// Reads the vault balance and the outstanding principal and yield, including deposits not
+	// yet tokenized, for `assert_solvency`
+	fn assert_vault_solvency(
+		lysergic_tokenizer_state: &TokenizerState,
+		underlying_vault_account: &AccountInfo,
+		principal_token_mint_account: &AccountInfo,
+		yield_token_mint_account: &AccountInfo,
+	) -> ProgramResult {
+		let vault_balance = load_token_account(underlying_vault_account)?.amount;
+		let principal_supply = lysergic_tokenizer_state
+			.principal_outstanding(load_mint(principal_token_mint_account)?.supply)?;
+		let yield_supply = lysergic_tokenizer_state
+			.yield_outstanding(load_mint(yield_token_mint_account)?.supply)?;
+
+		Self::assert_solvency(
+			lysergic_tokenizer_state,
+			vault_balance,
+			principal_supply,
+			simulate_claim_yield(vault_balance, principal_supply, yield_supply),
+		)
+	}
 
-		if underlying_vault_account.key != &lysergic_tokenizer_state.underlying_vault {
-			return Err(TokenizerError::IncorrectVaultAddress.into());
+	// Rates and redemption terms are fixed once any principal or yield is outstanding, as its
+	// holders tokenized under the existing ones
+	fn assert_no_outstanding_tokens(
+		lysergic_tokenizer_state: &TokenizerState,
+		principal_token_mint_account: &AccountInfo,
+		yield_token_mint_account: &AccountInfo,
+	) -> ProgramResult {
+		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
 		}
 
 		if yield_token_mint_account.key != &lysergic_tokenizer_state.yield_token_mint {
 			return Err(TokenizerError::IncorrectYieldMintAddress.into());
 		}
 
-		if !user_account.is_signer {
-			return Err(ProgramError::MissingRequiredSignature);
+		if lysergic_tokenizer_state.untokenized_principal != 0
+			|| lysergic_tokenizer_state.untokenized_yield != 0
+		{
+			return Err(TokenizerError::TokenizerHasDeposits.into());
 		}
 
-		if user_underlying_token_account.key
-			!= &spl_associated_token_account::get_associated_token_address(
-				user_account.key,
-				&lysergic_tokenizer_state.underlying_mint,
-			) {
-			return Err(TokenizerError::InvalidUserAccount.into());
+		// Nothing can have been tokenized before the mints exist
+		if !lysergic_tokenizer_state.mints_initialized {
+			return Ok(());
 		}
 
-		if user_yield_token_account.key
-			!= &spl_associated_token_account::get_associated_token_address(
-				user_account.key,
-				&lysergic_tokenizer_state.yield_token_mint,
-			) {
-			return Err(TokenizerError::InvalidUserAccount.into());
+		if load_mint(principal_token_mint_account)?.supply != 0
+			|| load_mint(yield_token_mint_account)?.supply != 0
+		{
+			return Err(TokenizerError::TokenizerHasDeposits.into());
 		}
 
-		if token_program.key != &spl_token::id() {
-			return Err(ProgramError::IncorrectProgramId);
-		}
+		Ok(())
+	}
 
-		// Check if the user has enough yield tokens to redeem
-		if spl_token::state::Account::unpack_from_slice(&user_yield_token_account.data.borrow())?
-			.amount < amount
-		{
-			return Err(TokenizerError::InsufficientFunds.into());
+	// Terminates one of the tokenizer's mints by revoking its mint authority, as SPL Token
+	// cannot close a mint. The mint account and its rent stay behind. Every token must have
+	// been redeemed first, as nothing backs them once the vault is swept. Does nothing if the
+	// authority was already revoked.
+	fn revoke_mint_authority<'a>(
+		lysergic_tokenizer_account: &AccountInfo<'a>,
+		lysergic_tokenizer_state: &TokenizerState,
+		mint_account: &AccountInfo<'a>,
+		token_program: &AccountInfo<'a>,
+	) -> ProgramResult {
+		let mint_data = load_mint(mint_account)?;
+
+		if mint_data.supply != 0 {
+			msg!(
+				"Mint {} still has {} outstanding",
+				mint_account.key,
+				mint_data.supply
+			);
+			return Err(TokenizerError::MintSupplyNotZero.into());
 		}
 
-		// In the rather unlikely event that a user does not have an underlying token account;
-		// create one for them
-		if user_underlying_token_account.owner != token_program.key {
-			let system_program = next_account_info(account_info_iter)?;
+		if mint_data.mint_authority.is_none() {
+			return Ok(());
+		}
 
-			if system_program.key != &system_program::id() {
-				return Err(ProgramError::IncorrectProgramId);
-			}
+		assert_mint_authority(&mint_data, lysergic_tokenizer_account.key)?;
 
-			invoke(
-				&spl_associated_token_account::instruction::create_associated_token_account(
-					user_account.key,
-					user_underlying_token_account.key,
-					&lysergic_tokenizer_state.underlying_mint,
-					user_account.key,
-				),
-				&[
-					user_underlying_token_account.clone(),
-					user_account.clone(),
-					underlying_mint_account.clone(),
-					token_program.clone(),
-					system_program.clone(),
-				],
-			)?;
+		invoke_signed(
+			&spl_token::instruction::set_authority(
+				token_program.key,
+				mint_account.key,
+				None,
+				spl_token::instruction::AuthorityType::MintTokens,
+				lysergic_tokenizer_account.key,
+				&[],
+			)?,
+			&[
+				mint_account.clone(),
+				lysergic_tokenizer_account.clone(),
+				token_program.clone(),
+			],
+			&[&[
+				b"tokenizer",
+				&lysergic_tokenizer_state.underlying_mint.to_bytes()[..],
+				&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
+				&[lysergic_tokenizer_state.bump],
+			]],
+		)
+	}
+
+	// Create the user's associated token account for one of the tokenizer's mints
+	fn create_user_token_account<'a>(
+		mint_account: &AccountInfo<'a>,
+		user_account: &AccountInfo<'a>,
+		user_token_account: &AccountInfo<'a>,
+		token_program: &AccountInfo<'a>,
+		system_program: &AccountInfo<'a>,
+		atoken_program: &AccountInfo<'a>,
+	) -> ProgramResult {
+		if system_program.key != &system_program::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if atoken_program.key != &spl_associated_token_account::id() {
+			return Err(ProgramError::IncorrectProgramId);
 		}
 
 		invoke(
-			&spl_token::instruction::burn(
-				token_program.key,
-				user_yield_token_account.key,
-				yield_token_mint_account.key,
+			&spl_associated_token_account::instruction::create_associated_token_account(
 				user_account.key,
-				&[],
-				amount,
-			)?,
+				user_account.key,
+				mint_account.key,
+				token_program.key,
+			),
 			&[
-				user_yield_token_account.clone(),
-				yield_token_mint_account.clone(),
 				user_account.clone(),
+				user_token_account.clone(),
+				user_account.clone(),
+				mint_account.clone(),
+				system_program.clone(),
 				token_program.clone(),
+				atoken_program.clone(),
+			],
+		)
+	}
+
+	// Load the user's position in the tokenizer, creating it the first time the user tokenizes
+	// or claims. The caller is responsible for persisting the updated position.
+	fn load_user_position<'a>(
+		lysergic_tokenizer_account: &AccountInfo<'a>,
+		user_account: &AccountInfo<'a>,
+		user_position_account: &AccountInfo<'a>,
+		system_program: &AccountInfo<'a>,
+	) -> Result<UserPosition, ProgramError> {
+		let (user_position_key, bump) =
+			get_user_position_address(lysergic_tokenizer_account.key, user_account.key);
+
+		if user_position_account.key != &user_position_key {
+			return Err(TokenizerError::InvalidUserAccount.into());
+		}
+
+		if user_position_account.owner == &crate::id() {
+			return UserPosition::unpack_initialized(&user_position_account.data.borrow()[..]);
+		}
+
+		if system_program.key != &system_program::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		msg!("No user position found, creating...");
+		let required_lamports = rent::Rent::get()?
+			.minimum_balance(USER_POSITION_SIZE)
+			.max(1)
+			.saturating_sub(user_position_account.lamports());
+
+		invoke_signed(
+			&system_instruction::create_account(
+				user_account.key,
+				&user_position_key,
+				required_lamports,
+				USER_POSITION_SIZE as u64,
+				&crate::id(),
+			),
+			&[
+				user_account.clone(),
+				user_position_account.clone(),
+				system_program.clone(),
 			],
+			&[&[
+				b"position",
+				&lysergic_tokenizer_account.key.to_bytes()[..],
+				&user_account.key.to_bytes()[..],
+				&[bump],
+			]],
 		)?;
 
+		Ok(UserPosition {
+			is_initialized: true,
+			bump,
+			tokenizer: *lysergic_tokenizer_account.key,
+			user: *user_account.key,
+			principal_minted: 0,
+			yield_claimed: 0,
+			deposit_timestamp: 0,
+			last_deposit_timestamp: 0,
+			untokenized_principal: 0,
+			untokenized_yield: 0,
+		})
+	}
+
+	// Mint principal or yield tokens to the user, signed by the tokenizer
+	fn mint_to_user<'a>(
+		lysergic_tokenizer_state: &TokenizerState,
+		lysergic_tokenizer_account: &AccountInfo<'a>,
+		mint_account: &AccountInfo<'a>,
+		user_token_account: &AccountInfo<'a>,
+		token_program: &AccountInfo<'a>,
+		amount: u64,
+	) -> ProgramResult {
 		invoke_signed(
-			&spl_token::instruction::transfer(
+			&spl_token::instruction::mint_to(
 				token_program.key,
-				underlying_vault_account.key,
-				user_underlying_token_account.key,
+				mint_account.key,
+				user_token_account.key,
 				lysergic_tokenizer_account.key,
 				&[],
 				amount,
 			)?,
 			&[
-				underlying_vault_account.clone(),
-				user_underlying_token_account.clone(),
+				mint_account.clone(),
+				user_token_account.clone(),
 				lysergic_tokenizer_account.clone(),
+				token_program.clone(),
 			],
 			&[&[
 				b"tokenizer",
-				&underlying_mint_account.key.to_bytes()[..],
+				&lysergic_tokenizer_state.underlying_mint.to_bytes()[..],
 				&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
 				&[lysergic_tokenizer_state.bump],
 			]],
-		)?;
-
-		Ok(())
+		)
 	}
 
-	fn process_terminate(accounts: &[AccountInfo]) -> ProgramResult {
+	fn process_redeem_principal_and_yield(
+		accounts: &[AccountInfo],
+		redemption_mode: RedemptionMode,
+		amount: u64,
+		min_total_underlying_out: u64,
+		unwrap: bool,
+		redeem_order: RedeemOrder,
+	) -> ProgramResult {
+		msg!("Redeem principal and yield...");
 		let account_info_iter = &mut accounts.iter();
 		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
-		let authority = next_account_info(account_info_iter)?;
 		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let underlying_mint_account = next_account_info(account_info_iter)?;
 		let principal_token_mint_account = next_account_info(account_info_iter)?;
 		let yield_token_mint_account = next_account_info(account_info_iter)?;
+		let user_account = next_account_info(account_info_iter)?;
+		let user_underlying_token_account = next_account_info(account_info_iter)?;
+		let user_principal_token_account = next_account_info(account_info_iter)?;
+		let user_yield_token_account = next_account_info(account_info_iter)?;
 		let token_program = next_account_info(account_info_iter)?;
 		let system_program = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+		let user_position_account = next_account_info(account_info_iter)?;
 
-		let terminate_tokenizer_accounts = [
+		let redeem_principal_accounts = [
 			lysergic_tokenizer_account.clone(),
-			authority.clone(),
 			underlying_vault_account.clone(),
+			underlying_mint_account.clone(),
+			principal_token_mint_account.clone(),
+			user_account.clone(),
+			user_underlying_token_account.clone(),
+			user_principal_token_account.clone(),
 			token_program.clone(),
 			system_program.clone(),
+			atoken_program.clone(),
+			yield_token_mint_account.clone(),
 		];
 
-		let terminate_mint_accounts = [
+		let claim_yield_accounts = [
 			lysergic_tokenizer_account.clone(),
-			authority.clone(),
+			underlying_vault_account.clone(),
+			underlying_mint_account.clone(),
 			principal_token_mint_account.clone(),
 			yield_token_mint_account.clone(),
+			user_account.clone(),
+			user_underlying_token_account.clone(),
+			user_yield_token_account.clone(),
 			token_program.clone(),
 			system_program.clone(),
+			atoken_program.clone(),
+			user_position_account.clone(),
 		];
 
-		Self::process_terminate_mints(&terminate_tokenizer_accounts)?;
-		Self::process_terminate_lysergic_tokenizer(&terminate_mint_accounts)?;
+		// The principal leg never pays out of the surplus and the yield leg only pays out of it,
+		// so neither leg changes what the other pays and the payout does not depend on the
+		// order. The whole yield leg is burned even when its payout is capped.
+		// Only unwrap once both transfers have landed in the user's underlying account.
+		let (principal_redeemed, yield_claimed) = match redeem_order {
+			RedeemOrder::PrincipalFirst => {
+				let principal_redeemed = Self::process_redeem_principal(
+					&redeem_principal_accounts,
+					redemption_mode,
+					amount,
+					false,
+				)?;
+				let yield_claimed =
+					Self::process_claim_yield(&claim_yield_accounts, amount, false, true, unwrap)?;
+				(principal_redeemed, yield_claimed)
+			}
+			RedeemOrder::YieldFirst => {
+				let yield_claimed =
+					Self::process_claim_yield(&claim_yield_accounts, amount, false, true, false)?;
+				let principal_redeemed = Self::process_redeem_principal(
+					&redeem_principal_accounts,
+					redemption_mode,
+					amount,
+					unwrap,
+				)?;
+				(principal_redeemed, yield_claimed)
+			}
+		};
+
+		// Principal is redeemed 1:1 unless the underlying rebases or this is an unwind, the yield
+		// leg may have been capped
+		if principal_redeemed.saturating_add(yield_claimed) < min_total_underlying_out {
+			return Err(TokenizerError::SlippageExceeded.into());
+		}
 
 		Ok(())
 	}
 
-	fn process_terminate_lysergic_tokenizer(accounts: &[AccountInfo]) -> ProgramResult {
-		let account_info_iter = &mut accounts.iter();
-		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
-		let authority = next_account_info(account_info_iter)?;
-		let underlying_vault_account = next_account_info(account_info_iter)?;
-		let token_program = next_account_info(account_info_iter)?;
-		let system_program = next_account_info(account_info_iter)?;
-
-		if lysergic_tokenizer_account.owner != &crate::id() {
-			return Err(TokenizerError::TokenizerNotInitialized.into());
+	fn process_unwind(
+		accounts: &[AccountInfo],
+		fraction_bps: u16,
+		min_total_underlying_out: u64,
+	) -> ProgramResult {
+		if fraction_bps == 0 || fraction_bps as u64 > BPS_DENOMINATOR {
+			return Err(ProgramError::InvalidArgument);
 		}
 
-		if !authority.is_signer {
-			return Err(ProgramError::MissingRequiredSignature);
-		}
+		// The token accounts themselves are validated by the redemption below
+		let user_principal_token_account =
+			accounts.get(7).ok_or(ProgramError::NotEnoughAccountKeys)?;
+		let user_yield_token_account = accounts.get(8).ok_or(ProgramError::NotEnoughAccountKeys)?;
 
-		let lysergic_tokenizer_state =
-			TokenizerState::try_from_slice(&lysergic_tokenizer_account.data.borrow()[..])?;
+		let matched = load_token_account(user_principal_token_account)?
+			.amount
+			.min(load_token_account(user_yield_token_account)?.amount);
+		let amount = mul_div_floor(matched, fraction_bps as u64, BPS_DENOMINATOR)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
 
-		if authority.key != &lysergic_tokenizer_state.authority {
-			return Err(TokenizerError::Unauthorised.into());
+		if amount == 0 {
+			return Err(TokenizerError::InsufficientFunds.into());
 		}
 
-		if lysergic_tokenizer_state.expiry_date >= clock::Clock::get()?.unix_timestamp {
-			return Err(TokenizerError::ExpiryDateNotElapsed.into());
-		}
+		msg!(
+			"Unwinding {} of {} matched principal and yield",
+			amount,
+			matched
+		);
+		// Settle the yield first, so the discount left in the vault by the principal leg is not
+		// claimed straight back by the same unwind
+		Self::process_redeem_principal_and_yield(
+			accounts,
+			RedemptionMode::Unwind,
+			amount,
+			min_total_underlying_out,
+			false,
+			RedeemOrder::YieldFirst,
+		)
+	}
 
-		// Check vault is empty
-		if spl_token::state::Account::unpack_from_slice(&underlying_vault_account.data.borrow())?
-			.amount != 0
-		{
-			return Err(TokenizerError::VaultNotEmpty.into());
-		}
+	fn process_redeem_mature_principal(
+		accounts: &[AccountInfo],
+		amount: u64,
+		unwrap: bool,
+		close_principal_ata: bool,
+	) -> ProgramResult {
+		Self::process_redeem_principal(accounts, RedemptionMode::Mature, amount, unwrap)?;
 
-		if underlying_vault_account.key != &lysergic_tokenizer_state.underlying_vault {
-			return Err(TokenizerError::IncorrectVaultAddress.into());
+		if !close_principal_ata {
+			return Ok(());
 		}
 
-		if token_program.key != &spl_token::id() {
-			return Err(ProgramError::IncorrectProgramId);
-		}
+		// The accounts were validated by the redemption above
+		let user_account = &accounts[4];
+		let user_principal_token_account = &accounts[6];
+		let token_program = &accounts[7];
 
-		if system_program.key != &system_program::id() {
-			return Err(ProgramError::IncorrectProgramId);
+		if load_token_account(user_principal_token_account)?.amount != 0 {
+			return Err(TokenizerError::TokenAccountNotEmpty.into());
 		}
 
-		invoke_signed(
-			&system_instruction::transfer(
-				lysergic_tokenizer_account.key,
-				authority.key,
-				match lysergic_tokenizer_account
-					.lamports
-					.as_ref()
-					.try_borrow()
-					.as_deref()
-				{
-					Ok(lamports) => **lamports,
-					Err(_) => return Err(ProgramError::InvalidAccountData),
-				},
-			),
+		msg!("Closing principal token account...");
+		invoke(
+			&spl_token::instruction::close_account(
+				token_program.key,
+				user_principal_token_account.key,
+				user_account.key,
+				user_account.key,
+				&[],
+			)?,
 			&[
-				lysergic_tokenizer_account.clone(),
-				authority.clone(),
-				system_program.clone(),
+				user_principal_token_account.clone(),
+				user_account.clone(),
+				user_account.clone(),
+				token_program.clone(),
 			],
-			&[&[
-				b"tokenizer",
-				&lysergic_tokenizer_state.underlying_mint.to_bytes()[..],
-				&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
-				&[lysergic_tokenizer_state.bump],
-			]],
-		)?;
-
-		// Terminate the Lysergic tokenizer account
-		lysergic_tokenizer_account.assign(&system_program::id());
-		lysergic_tokenizer_account.realloc(0, false)?;
-
-		Ok(())
+		)
 	}
 
-	fn process_terminate_mints(accounts: &[AccountInfo]) -> ProgramResult {
+	// Returns the amount of underlying paid out
+	fn process_redeem_principal(
+		accounts: &[AccountInfo],
+		redemption_mode: RedemptionMode,
+		amount: u64,
+		unwrap: bool,
+	) -> Result<u64, ProgramError> {
+		msg!("Redeeming principal...");
 		let account_info_iter = &mut accounts.iter();
 		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
-		let authority = next_account_info(account_info_iter)?;
+		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let underlying_mint_account = next_account_info(account_info_iter)?;
 		let principal_token_mint_account = next_account_info(account_info_iter)?;
-		let yield_token_mint_account = next_account_info(account_info_iter)?;
+		let user_account = next_account_info(account_info_iter)?;
+		let user_underlying_token_account = next_account_info(account_info_iter)?;
+		let user_principal_token_account = next_account_info(account_info_iter)?;
 		let token_program = next_account_info(account_info_iter)?;
 		let system_program = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
 
 		if lysergic_tokenizer_account.owner != &crate::id() {
 			return Err(TokenizerError::TokenizerNotInitialized.into());
 		}
 
-		if !authority.is_signer {
-			return Err(ProgramError::MissingRequiredSignature);
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if let RedemptionMode::Mature = redemption_mode {
+			if lysergic_tokenizer_state.expiry_date >= clock::Clock::get()?.unix_timestamp {
+				return Err(TokenizerError::ExpiryDateNotElapsed.into());
+			}
 		}
 
-		let lysergic_tokenizer_state =
-			TokenizerState::try_from_slice(&lysergic_tokenizer_account.data.borrow()[..])?;
+		if underlying_vault_account.owner != &lysergic_tokenizer_state.token_program {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
 
-		if authority.key != &lysergic_tokenizer_state.authority {
-			return Err(TokenizerError::Unauthorised.into());
+		if underlying_vault_account.key != &lysergic_tokenizer_state.underlying_vault {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
 		}
 
-		if lysergic_tokenizer_state.expiry_date >= clock::Clock::get()?.unix_timestamp {
-			return Err(TokenizerError::ExpiryDateNotElapsed.into());
+		if underlying_mint_account.key != &lysergic_tokenizer_state.underlying_mint {
+			return Err(TokenizerError::IncorrectUnderlyingMintAddress.into());
 		}
 
 		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
@@ -1272,85 +1664,1811 @@ impl TokenizerProcessor {
 			return Err(TokenizerError::IncorrectYieldMintAddress.into());
 		}
 
-		if token_program.key != &spl_token::id() {
-			return Err(ProgramError::IncorrectProgramId);
-		}
-
-		if system_program.key != &system_program::id() {
-			return Err(ProgramError::IncorrectProgramId);
+		if !user_account.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
 		}
 
-		invoke_signed(
-			&spl_token::instruction::close_account(
+		if user_underlying_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				user_account.key,
+				&lysergic_tokenizer_state.underlying_mint,
+			) {
+			return Err(TokenizerError::InvalidUserAccount.into());
+		}
+
+		if user_principal_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				user_account.key,
+				&lysergic_tokenizer_state.principal_token_mint,
+			) {
+			return Err(TokenizerError::InvalidUserAccount.into());
+		}
+
+		if token_program.key != &lysergic_tokenizer_state.token_program {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		// Check if the user has enough principal tokens to redeem
+		if load_token_account(user_principal_token_account)?.amount < amount {
+			return Err(TokenizerError::InsufficientFunds.into());
+		}
+
+		// In the rather unlikely event that a user does not have an underlying token account;
+		// create one for them
+		if user_underlying_token_account.owner != token_program.key {
+			if system_program.key != &system_program::id() {
+				return Err(ProgramError::IncorrectProgramId);
+			}
+
+			if atoken_program.key != &spl_associated_token_account::id() {
+				return Err(ProgramError::IncorrectProgramId);
+			}
+
+			invoke(
+				&spl_associated_token_account::instruction::create_associated_token_account(
+					user_account.key,
+					user_account.key,
+					&lysergic_tokenizer_state.underlying_mint,
+					token_program.key,
+				),
+				&[
+					user_account.clone(),
+					user_underlying_token_account.clone(),
+					user_account.clone(),
+					underlying_mint_account.clone(),
+					system_program.clone(),
+					token_program.clone(),
+					atoken_program.clone(),
+				],
+			)?;
+		}
+
+		// A rebasing vault short of the outstanding principal is shared pro rata by it, read
+		// before the burn
+		let payout = if lysergic_tokenizer_state.rebasing {
+			simulate_redeem_principal(
+				load_token_account(underlying_vault_account)?.amount,
+				lysergic_tokenizer_state
+					.principal_outstanding(load_mint(principal_token_mint_account)?.supply)?,
+				amount,
+				true,
+			)
+			.ok_or(ProgramError::ArithmeticOverflow)?
+		} else {
+			amount
+		};
+
+		// Before maturity an unwind sells the principal back at its discounted price, leaving
+		// the rest in the vault as surplus for the yield holders
+		let payout = if let RedemptionMode::Unwind = redemption_mode {
+			let rate = exchange_rate(
+				lysergic_tokenizer_state.fixed_apy,
+				lysergic_tokenizer_state.expiry_date,
+				clock::Clock::get()?.unix_timestamp,
+			)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
+			mul_div_floor(payout, rate, RATE_SCALE).ok_or(ProgramError::ArithmeticOverflow)?
+		} else {
+			payout
+		};
+
+		invoke(
+			&spl_token::instruction::burn(
 				token_program.key,
+				user_principal_token_account.key,
 				principal_token_mint_account.key,
-				authority.key,
-				lysergic_tokenizer_account.key,
+				user_account.key,
 				&[],
+				amount,
 			)?,
 			&[
+				user_principal_token_account.clone(),
 				principal_token_mint_account.clone(),
-				authority.clone(),
-				lysergic_tokenizer_account.clone(),
+				user_account.clone(),
 				token_program.clone(),
 			],
-			&[&[
-				b"tokenizer",
-				&lysergic_tokenizer_state.underlying_mint.to_bytes()[..],
-				&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
-				&[lysergic_tokenizer_state.bump],
-			]],
 		)?;
 
 		invoke_signed(
-			&spl_token::instruction::close_account(
+			&spl_token::instruction::transfer(
 				token_program.key,
-				yield_token_mint_account.key,
-				authority.key,
+				underlying_vault_account.key,
+				user_underlying_token_account.key,
 				lysergic_tokenizer_account.key,
 				&[],
+				payout,
 			)?,
 			&[
-				yield_token_mint_account.clone(),
-				authority.clone(),
+				underlying_vault_account.clone(),
+				user_underlying_token_account.clone(),
 				lysergic_tokenizer_account.clone(),
-				token_program.clone(),
 			],
 			&[&[
 				b"tokenizer",
-				&lysergic_tokenizer_state.underlying_mint.to_bytes()[..],
+				&underlying_mint_account.key.to_bytes()[..],
 				&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
 				&[lysergic_tokenizer_state.bump],
 			]],
 		)?;
 
+		// Principal is only minted 1:1 from deposits, so redeemed principal never exceeds them
+		lysergic_tokenizer_state.total_deposited = lysergic_tokenizer_state
+			.total_deposited
+			.checked_sub(amount)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+		Self::assert_vault_solvency(
+			&lysergic_tokenizer_state,
+			underlying_vault_account,
+			principal_token_mint_account,
+			yield_token_mint_account,
+		)?;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
+
+		if unwrap {
+			Self::unwrap_native(
+				underlying_mint_account,
+				user_account,
+				user_underlying_token_account,
+				token_program,
+			)?;
+		}
+
+		Ok(payout)
+	}
+
+	// Returns the amount of underlying paid out. With `burn_full_amount` the whole `amount` of
+	// yield tokens is burned even if the payout is capped, for redemptions that pair it with
+	// principal paid out in full.
+	fn process_claim_yield(
+		accounts: &[AccountInfo],
+		amount: u64,
+		strict: bool,
+		burn_full_amount: bool,
+		unwrap: bool,
+	) -> Result<u64, ProgramError> {
+		msg!("Claiming yield...");
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let underlying_mint_account = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
+		let user_account = next_account_info(account_info_iter)?;
+		let user_underlying_token_account = next_account_info(account_info_iter)?;
+		let user_yield_token_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+		let user_position_account = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		let lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if underlying_vault_account.owner != &lysergic_tokenizer_state.token_program {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if underlying_vault_account.key != &lysergic_tokenizer_state.underlying_vault {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if underlying_mint_account.key != &lysergic_tokenizer_state.underlying_mint {
+			return Err(TokenizerError::IncorrectUnderlyingMintAddress.into());
+		}
+
+		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
+		}
+
+		if yield_token_mint_account.key != &lysergic_tokenizer_state.yield_token_mint {
+			return Err(TokenizerError::IncorrectYieldMintAddress.into());
+		}
+
+		if !user_account.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		if user_underlying_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				user_account.key,
+				&lysergic_tokenizer_state.underlying_mint,
+			) {
+			return Err(TokenizerError::InvalidUserAccount.into());
+		}
+
+		if user_yield_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				user_account.key,
+				&lysergic_tokenizer_state.yield_token_mint,
+			) {
+			return Err(TokenizerError::InvalidUserAccount.into());
+		}
+
+		if token_program.key != &lysergic_tokenizer_state.token_program {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		let user_yield_token_data = load_token_account(user_yield_token_account)?;
+
+		// The user burns the yield tokens, so they must own the account or be its delegate
+		let is_owner = &user_yield_token_data.owner == user_account.key;
+		let is_delegate = user_yield_token_data.delegate == COption::Some(*user_account.key)
+			&& user_yield_token_data.delegated_amount >= amount;
+
+		if !is_owner && !is_delegate {
+			return Err(TokenizerError::InvalidUserAccount.into());
+		}
+
+		// Check if the user has enough yield tokens to redeem
+		if user_yield_token_data.amount < amount {
+			return Err(TokenizerError::InsufficientFunds.into());
+		}
+
+		// The vault must always hold enough underlying to redeem the outstanding principal 1:1,
+		// including underlying deposited but not yet tokenized, so only the balance in excess of
+		// it can be claimed as yield
+		let vault_balance = load_token_account(underlying_vault_account)?.amount;
+		// Once the principal mint has been terminated its authority is revoked and its supply
+		// is zero, so there is no principal left to back
+		let principal_token_mint_data = load_mint(principal_token_mint_account)?;
+		if !lysergic_tokenizer_state.principal_mint_terminated {
+			assert_mint_authority(&principal_token_mint_data, lysergic_tokenizer_account.key)?;
+		}
+		let principal_outstanding =
+			lysergic_tokenizer_state.principal_outstanding(principal_token_mint_data.supply)?;
+		let claimable = simulate_claim_yield(vault_balance, principal_outstanding, amount);
+
+		if claimable < amount {
+			if strict {
+				return Err(TokenizerError::InsufficientFunds.into());
+			}
+			msg!(
+				"Claim capped to {} to preserve principal backing",
+				claimable
+			);
+		}
+		let burn_amount = if burn_full_amount { amount } else { claimable };
+
+		let mut user_position = Self::load_user_position(
+			lysergic_tokenizer_account,
+			user_account,
+			user_position_account,
+			system_program,
+		)?;
+
+		// Yield cannot be claimed in the same breath as tokenizing, which would let a single
+		// transaction tokenize, move the rate and claim against it
+		let held_seconds = clock::Clock::get()?
+			.unix_timestamp
+			.saturating_sub(user_position.last_deposit_timestamp);
+		if held_seconds < lysergic_tokenizer_state.min_hold_seconds {
+			return Err(TokenizerError::CooldownActive.into());
+		}
+
+		// In the rather unlikely event that a user does not have an underlying token account;
+		// create one for them
+		if user_underlying_token_account.owner != token_program.key {
+			if system_program.key != &system_program::id() {
+				return Err(ProgramError::IncorrectProgramId);
+			}
+
+			if atoken_program.key != &spl_associated_token_account::id() {
+				return Err(ProgramError::IncorrectProgramId);
+			}
+
+			invoke(
+				&spl_associated_token_account::instruction::create_associated_token_account(
+					user_account.key,
+					user_account.key,
+					&lysergic_tokenizer_state.underlying_mint,
+					token_program.key,
+				),
+				&[
+					user_account.clone(),
+					user_underlying_token_account.clone(),
+					user_account.clone(),
+					underlying_mint_account.clone(),
+					system_program.clone(),
+					token_program.clone(),
+					atoken_program.clone(),
+				],
+			)?;
+		}
+
+		invoke(
+			&spl_token::instruction::burn(
+				token_program.key,
+				user_yield_token_account.key,
+				yield_token_mint_account.key,
+				user_account.key,
+				&[],
+				burn_amount,
+			)?,
+			&[
+				user_yield_token_account.clone(),
+				yield_token_mint_account.clone(),
+				user_account.clone(),
+				token_program.clone(),
+			],
+		)?;
+
 		invoke_signed(
-			&system_instruction::transfer(
+			&spl_token::instruction::transfer(
+				token_program.key,
+				underlying_vault_account.key,
+				user_underlying_token_account.key,
 				lysergic_tokenizer_account.key,
-				authority.key,
-				match lysergic_tokenizer_account
-					.lamports
-					.as_ref()
-					.try_borrow()
-					.as_deref()
-				{
-					Ok(lamports) => **lamports,
-					Err(_) => return Err(ProgramError::InvalidAccountData),
-				},
-			),
+				&[],
+				claimable,
+			)?,
 			&[
+				underlying_vault_account.clone(),
+				user_underlying_token_account.clone(),
 				lysergic_tokenizer_account.clone(),
-				authority.clone(),
-				system_program.clone(),
 			],
 			&[&[
 				b"tokenizer",
-				&lysergic_tokenizer_state.underlying_mint.to_bytes()[..],
+				&underlying_mint_account.key.to_bytes()[..],
 				&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
 				&[lysergic_tokenizer_state.bump],
 			]],
 		)?;
 
+		Self::assert_vault_solvency(
+			&lysergic_tokenizer_state,
+			underlying_vault_account,
+			principal_token_mint_account,
+			yield_token_mint_account,
+		)?;
+
+		user_position.record_yield_claimed(claimable)?;
+		user_position.serialize(&mut &mut user_position_account.data.borrow_mut()[..])?;
+
+		// Event for indexers, see `cli/examples/indexer.rs`
+		msg!(
+			"ClaimYield {} {} {}",
+			lysergic_tokenizer_account.key,
+			user_account.key,
+			claimable
+		);
+
+		if unwrap {
+			Self::unwrap_native(
+				underlying_mint_account,
+				user_account,
+				user_underlying_token_account,
+				token_program,
+			)?;
+		}
+
+		set_return_data(&claimable.to_le_bytes());
+
+		Ok(claimable)
+	}
+
+	// Close the user's wrapped SOL account so its lamports, including any wrapped SOL it
+	// already held, are returned to the user as native SOL. Does nothing for other underlyings.
+	fn unwrap_native<'a>(
+		underlying_mint_account: &AccountInfo<'a>,
+		user_account: &AccountInfo<'a>,
+		user_underlying_token_account: &AccountInfo<'a>,
+		token_program: &AccountInfo<'a>,
+	) -> ProgramResult {
+		if underlying_mint_account.key != &spl_token::native_mint::id() {
+			return Ok(());
+		}
+
+		msg!("Unwrapping native SOL...");
+		invoke(
+			&spl_token::instruction::close_account(
+				token_program.key,
+				user_underlying_token_account.key,
+				user_account.key,
+				user_account.key,
+				&[],
+			)?,
+			&[
+				user_underlying_token_account.clone(),
+				user_account.clone(),
+				user_account.clone(),
+				token_program.clone(),
+			],
+		)
+	}
+
+	fn process_terminate(accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let authority = next_account_info(account_info_iter)?;
+		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+		let underlying_mint_account = next_account_info(account_info_iter)?;
+		let authority_underlying_token_account = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+
+		let terminate_tokenizer_accounts = [
+			lysergic_tokenizer_account.clone(),
+			authority.clone(),
+			underlying_vault_account.clone(),
+			token_program.clone(),
+			system_program.clone(),
+			underlying_mint_account.clone(),
+			authority_underlying_token_account.clone(),
+			atoken_program.clone(),
+		];
+
+		let terminate_mint_accounts = [
+			lysergic_tokenizer_account.clone(),
+			authority.clone(),
+			principal_token_mint_account.clone(),
+			yield_token_mint_account.clone(),
+			token_program.clone(),
+			system_program.clone(),
+		];
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		let lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		// The mints must be terminated first as the tokenizer is their authority. They may
+		// already have been terminated by `TerminateMints`, in which case only the tokenizer is
+		// left to close.
+		if !lysergic_tokenizer_state.mints_terminated {
+			Self::process_terminate_mints(&terminate_mint_accounts)?;
+		}
+		Self::process_terminate_lysergic_tokenizer(&terminate_tokenizer_accounts)?;
+
+		Ok(())
+	}
+
+	fn process_terminate_lysergic_tokenizer(accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let authority = next_account_info(account_info_iter)?;
+		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+		let underlying_mint_account = next_account_info(account_info_iter)?;
+		let authority_underlying_token_account = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if !authority.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if authority.key != &lysergic_tokenizer_state.authority {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		if !lysergic_tokenizer_state.mints_terminated {
+			return Err(TokenizerError::MintsNotTerminated.into());
+		}
+
+		if lysergic_tokenizer_state.expiry_date >= clock::Clock::get()?.unix_timestamp {
+			return Err(TokenizerError::ExpiryDateNotElapsed.into());
+		}
+
+		if underlying_vault_account.key != &lysergic_tokenizer_state.underlying_vault {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if underlying_mint_account.key != &lysergic_tokenizer_state.underlying_mint {
+			return Err(TokenizerError::IncorrectUnderlyingMintAddress.into());
+		}
+
+		if authority_underlying_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				authority.key,
+				&lysergic_tokenizer_state.underlying_mint,
+			) {
+			return Err(TokenizerError::InvalidUserAccount.into());
+		}
+
+		if token_program.key != &lysergic_tokenizer_state.token_program {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if system_program.key != &system_program::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		let tokenizer_seeds: &[&[u8]] = &[
+			b"tokenizer",
+			&lysergic_tokenizer_state.underlying_mint.to_bytes()[..],
+			&lysergic_tokenizer_state.expiry_date.to_le_bytes(),
+			&[lysergic_tokenizer_state.bump],
+		];
+
+		// A vault that is already closed has nothing left to sweep or close
+		let vault_closed = underlying_vault_account.data_is_empty();
+
+		// The mints were only terminated once their supply was zero and can no longer mint, so
+		// any dust left in the vault can no longer be redeemed; sweep it to the authority so
+		// the vault can be closed
+		let vault_balance = if vault_closed {
+			0
+		} else {
+			load_token_account(underlying_vault_account)?.amount
+		};
+		if vault_balance != 0 {
+			msg!("Sweeping {} underlying left in the vault...", vault_balance);
+			if authority_underlying_token_account.owner != token_program.key {
+				Self::create_user_token_account(
+					underlying_mint_account,
+					authority,
+					authority_underlying_token_account,
+					token_program,
+					system_program,
+					atoken_program,
+				)?;
+			}
+
+			invoke_signed(
+				&spl_token::instruction::transfer(
+					token_program.key,
+					underlying_vault_account.key,
+					authority_underlying_token_account.key,
+					lysergic_tokenizer_account.key,
+					&[],
+					vault_balance,
+				)?,
+				&[
+					underlying_vault_account.clone(),
+					authority_underlying_token_account.clone(),
+					lysergic_tokenizer_account.clone(),
+				],
+				&[tokenizer_seeds],
+			)?;
+		}
+
+		// Close the vault, returning its rent to the authority
+		if !vault_closed {
+			invoke_signed(
+				&spl_token::instruction::close_account(
+					token_program.key,
+					underlying_vault_account.key,
+					authority.key,
+					lysergic_tokenizer_account.key,
+					&[],
+				)?,
+				&[
+					underlying_vault_account.clone(),
+					authority.clone(),
+					lysergic_tokenizer_account.clone(),
+				],
+				&[tokenizer_seeds],
+			)?;
+		}
+
+		// The tokenizer account holds data, so the system program cannot transfer from it; as
+		// its owner we move the lamports directly, exactly once, after the vault rent above
+		let tokenizer_lamports = lysergic_tokenizer_account.lamports();
+		**authority.try_borrow_mut_lamports()? = authority
+			.lamports()
+			.checked_add(tokenizer_lamports)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+		**lysergic_tokenizer_account.try_borrow_mut_lamports()? = 0;
+
+		// Clear the discriminator so the account can never be mistaken for a live tokenizer
+		lysergic_tokenizer_state.is_initialized = false;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
+
+		// Terminate the Lysergic tokenizer account, its data must be gone before it can be
+		// handed back to the system program
+		lysergic_tokenizer_account.realloc(0, false)?;
+		lysergic_tokenizer_account.assign(&system_program::id());
+
+		Ok(())
+	}
+
+	fn process_terminate_mints(accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let authority = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if !authority.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if authority.key != &lysergic_tokenizer_state.authority {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		if lysergic_tokenizer_state.expiry_date >= clock::Clock::get()?.unix_timestamp {
+			return Err(TokenizerError::ExpiryDateNotElapsed.into());
+		}
+
+		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
+		}
+
+		if yield_token_mint_account.key != &lysergic_tokenizer_state.yield_token_mint {
+			return Err(TokenizerError::IncorrectYieldMintAddress.into());
+		}
+
+		if token_program.key != &lysergic_tokenizer_state.token_program {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if system_program.key != &system_program::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		// Underlying deposited but not yet tokenized could no longer be
+		if lysergic_tokenizer_state.untokenized_principal != 0
+			|| lysergic_tokenizer_state.untokenized_yield != 0
+		{
+			return Err(TokenizerError::TokenizerHasDeposits.into());
+		}
+
+		// The principal mint may already have been terminated by `TerminatePrincipalMint`, in
+		// which case revoking is skipped, so an interrupted termination can be re-run
+		Self::revoke_mint_authority(
+			lysergic_tokenizer_account,
+			&lysergic_tokenizer_state,
+			principal_token_mint_account,
+			token_program,
+		)?;
+		Self::revoke_mint_authority(
+			lysergic_tokenizer_account,
+			&lysergic_tokenizer_state,
+			yield_token_mint_account,
+			token_program,
+		)?;
+
+		// The tokenizer account stays open so it can be terminated afterwards
+		lysergic_tokenizer_state.principal_mint_terminated = true;
+		lysergic_tokenizer_state.mints_terminated = true;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	fn process_get_exchange_rate(accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		let lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		let rate = exchange_rate(
+			lysergic_tokenizer_state.fixed_apy,
+			lysergic_tokenizer_state.expiry_date,
+			clock::Clock::get()?.unix_timestamp,
+		)
+		.ok_or(ProgramError::ArithmeticOverflow)?;
+
+		msg!("Exchange rate: {}", rate);
+		set_return_data(&rate.to_le_bytes());
+
+		Ok(())
+	}
+
+	fn process_reconcile(accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		let lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if underlying_vault_account.key != &lysergic_tokenizer_state.underlying_vault {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
+		}
+
+		if yield_token_mint_account.key != &lysergic_tokenizer_state.yield_token_mint {
+			return Err(TokenizerError::IncorrectYieldMintAddress.into());
+		}
+
+		let vault_balance = load_token_account(underlying_vault_account)?.amount;
+		let principal_supply = load_mint(principal_token_mint_account)?.supply;
+		let yield_supply = load_mint(yield_token_mint_account)?.supply;
+		let principal_outstanding =
+			lysergic_tokenizer_state.principal_outstanding(principal_supply)?;
+		let yield_outstanding = lysergic_tokenizer_state.yield_outstanding(yield_supply)?;
+
+		// The vault must back principal 1:1, as `assert_solvency` requires unless a rebasing
+		// vault has lost value, which is reported here as well. Principal and yield are minted together, so before maturity the two are outstanding
+		// in lockstep; yield burned apart from its principal, by a claim or by its holder,
+		// shows up here too.
+		let matured = lysergic_tokenizer_state.expiry_date < clock::Clock::get()?.unix_timestamp;
+		let healthy = vault_balance >= principal_outstanding
+			&& (matured || principal_outstanding == yield_outstanding)
+			&& yield_outstanding <= lysergic_tokenizer_state.total_deposited;
+
+		msg!(
+			"Healthy: {}, vault: {}, principal supply: {}, yield supply: {}",
+			healthy,
+			vault_balance,
+			principal_supply,
+			yield_supply
+		);
+
+		let mut data = Vec::with_capacity(1 + 8 * 3);
+		data.push(healthy as u8);
+		data.extend_from_slice(&vault_balance.to_le_bytes());
+		data.extend_from_slice(&principal_supply.to_le_bytes());
+		data.extend_from_slice(&yield_supply.to_le_bytes());
+		set_return_data(&data);
+
+		Ok(())
+	}
+
+	fn process_recreate_vault(accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let authority = next_account_info(account_info_iter)?;
+		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let underlying_mint_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if !authority.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if authority.key != &lysergic_tokenizer_state.authority {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		if underlying_vault_account.key != &lysergic_tokenizer_state.underlying_vault {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if underlying_mint_account.key != &lysergic_tokenizer_state.underlying_mint {
+			return Err(TokenizerError::IncorrectUnderlyingMintAddress.into());
+		}
+
+		if token_program.key != &lysergic_tokenizer_state.token_program {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if system_program.key != &system_program::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if atoken_program.key != &spl_associated_token_account::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		// Only a vault that has been closed can be recreated
+		if underlying_vault_account.owner != &system_program::id()
+			|| underlying_vault_account.lamports() != 0
+		{
+			return Err(ProgramError::AccountAlreadyInitialized);
+		}
+
+		msg!("Recreating underlying vault account");
+		invoke(
+			&spl_associated_token_account::instruction::create_associated_token_account(
+				authority.key,
+				lysergic_tokenizer_account.key,
+				&lysergic_tokenizer_state.underlying_mint,
+				token_program.key,
+			),
+			&[
+				authority.clone(),
+				underlying_vault_account.clone(),
+				lysergic_tokenizer_account.clone(),
+				underlying_mint_account.clone(),
+				system_program.clone(),
+				token_program.clone(),
+				atoken_program.clone(),
+			],
+		)?;
+
+		Ok(())
+	}
+
+	fn process_update_fixed_apy(accounts: &[AccountInfo], fixed_apy: u64) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let authority = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if !authority.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if authority.key != &lysergic_tokenizer_state.authority {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		// The expiry date is never updatable as it is part of the tokenizer's address
+		Self::assert_no_outstanding_tokens(
+			&lysergic_tokenizer_state,
+			principal_token_mint_account,
+			yield_token_mint_account,
+		)?;
+
+		validate_fixed_apy(
+			fixed_apy,
+			lysergic_tokenizer_state.expiry_date,
+			clock::Clock::get()?.unix_timestamp,
+		)?;
+
+		lysergic_tokenizer_state.fixed_apy = fixed_apy;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	fn process_update_min_hold(accounts: &[AccountInfo], min_hold_seconds: i64) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let authority = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if !authority.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if authority.key != &lysergic_tokenizer_state.authority {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		if min_hold_seconds < 0 {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		lysergic_tokenizer_state.min_hold_seconds = min_hold_seconds;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	fn process_update_rebasing(accounts: &[AccountInfo], rebasing: bool) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let authority = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if !authority.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if authority.key != &lysergic_tokenizer_state.authority {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		// Switching how principal is redeemed would change what existing holders are owed
+		Self::assert_no_outstanding_tokens(
+			&lysergic_tokenizer_state,
+			principal_token_mint_account,
+			yield_token_mint_account,
+		)?;
+
+		lysergic_tokenizer_state.rebasing = rebasing;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	fn process_init_config(
+		accounts: &[AccountInfo],
+		min_tenor: i64,
+		max_tenor: i64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let program_config_account = next_account_info(account_info_iter)?;
+		let admin = next_account_info(account_info_iter)?;
+		let program_data_account = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+
+		let (program_config_key, bump) = get_program_config_address();
+
+		if program_config_account.key != &program_config_key {
+			return Err(ProgramError::InvalidSeeds);
+		}
+
+		if !admin.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		// Only the upgrade authority may create the config, otherwise anyone could front-run
+		// the deployment and make themselves admin
+		if Self::upgrade_authority(program_data_account)? != Some(*admin.key) {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		if system_program.key != &system_program::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if min_tenor > max_tenor {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		if program_config_account.owner == &crate::id() {
+			return Err(ProgramError::AccountAlreadyInitialized);
+		}
+
+		let required_lamports = rent::Rent::get()?
+			.minimum_balance(CONFIG_SIZE)
+			.max(1)
+			.saturating_sub(program_config_account.lamports());
+
+		invoke_signed(
+			&system_instruction::create_account(
+				admin.key,
+				&program_config_key,
+				required_lamports,
+				CONFIG_SIZE as u64,
+				&crate::id(),
+			),
+			&[
+				admin.clone(),
+				program_config_account.clone(),
+				system_program.clone(),
+			],
+			&[&[b"config", &[bump]]],
+		)?;
+
+		let program_config = ProgramConfig {
+			is_initialized: true,
+			bump,
+			admin: *admin.key,
+			min_tenor,
+			max_tenor,
+		};
+
+		program_config.serialize(&mut &mut program_config_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	fn process_update_config(
+		accounts: &[AccountInfo],
+		min_tenor: i64,
+		max_tenor: i64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let program_config_account = next_account_info(account_info_iter)?;
+		let admin = next_account_info(account_info_iter)?;
+
+		if program_config_account.owner != &crate::id() {
+			return Err(TokenizerError::ConfigNotInitialized.into());
+		}
+
+		if !admin.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let mut program_config =
+			ProgramConfig::unpack_initialized(&program_config_account.data.borrow()[..])?;
+
+		if admin.key != &program_config.admin {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		if min_tenor > max_tenor {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		program_config.min_tenor = min_tenor;
+		program_config.max_tenor = max_tenor;
+		program_config.serialize(&mut &mut program_config_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	// Reads the upgrade authority from this program's program data account, which is laid out
+	// as a bincode `UpgradeableLoaderState::ProgramData`: a `u32` tag, the deployment slot and
+	// an optional authority
+	fn upgrade_authority(
+		program_data_account: &AccountInfo,
+	) -> Result<Option<Pubkey>, ProgramError> {
+		if program_data_account.key
+			!= &bpf_loader_upgradeable::get_program_data_address(&crate::id())
+			|| program_data_account.owner != &bpf_loader_upgradeable::id()
+		{
+			return Err(ProgramError::InvalidAccountData);
+		}
+
+		let data = program_data_account.data.borrow();
+		if data.len() < UpgradeableLoaderState::size_of_programdata_metadata()
+			|| data[..4] != 3u32.to_le_bytes()
+		{
+			return Err(ProgramError::InvalidAccountData);
+		}
+
+		match data[12] {
+			0 => Ok(None),
+			1 => Pubkey::try_from(&data[13..45])
+				.map(Some)
+				.map_err(|_| ProgramError::InvalidAccountData),
+			_ => Err(ProgramError::InvalidAccountData),
+		}
+	}
+
+	fn process_batch_redeem(accounts: &[AccountInfo], entries: &[(u64, u64)]) -> ProgramResult {
+		msg!("Batch redeeming {} entries...", entries.len());
+		if entries.is_empty() || entries.len() > MAX_BATCH_REDEEM_ENTRIES {
+			return Err(ProgramError::InvalidInstructionData);
+		}
+
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let underlying_vault_account = next_account_info(account_info_iter)?;
+		let underlying_mint_account = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let yield_token_mint_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+
+		for (principal_amount, yield_amount) in entries {
+			let user_account = next_account_info(account_info_iter)?;
+			let user_underlying_token_account = next_account_info(account_info_iter)?;
+			let user_principal_token_account = next_account_info(account_info_iter)?;
+			let user_yield_token_account = next_account_info(account_info_iter)?;
+			let user_position_account = next_account_info(account_info_iter)?;
+
+			if *principal_amount != 0 {
+				let redeem_principal_accounts = [
+					lysergic_tokenizer_account.clone(),
+					underlying_vault_account.clone(),
+					underlying_mint_account.clone(),
+					principal_token_mint_account.clone(),
+					user_account.clone(),
+					user_underlying_token_account.clone(),
+					user_principal_token_account.clone(),
+					token_program.clone(),
+					system_program.clone(),
+					atoken_program.clone(),
+					yield_token_mint_account.clone(),
+				];
+
+				Self::process_redeem_principal(
+					&redeem_principal_accounts,
+					RedemptionMode::Mature,
+					*principal_amount,
+					false,
+				)?;
+			}
+
+			if *yield_amount != 0 {
+				let claim_yield_accounts = [
+					lysergic_tokenizer_account.clone(),
+					underlying_vault_account.clone(),
+					underlying_mint_account.clone(),
+					principal_token_mint_account.clone(),
+					yield_token_mint_account.clone(),
+					user_account.clone(),
+					user_underlying_token_account.clone(),
+					user_yield_token_account.clone(),
+					token_program.clone(),
+					system_program.clone(),
+					atoken_program.clone(),
+					user_position_account.clone(),
+				];
+
+				Self::process_claim_yield(
+					&claim_yield_accounts,
+					*yield_amount,
+					false,
+					false,
+					false,
+				)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	fn process_terminate_principal_mint(accounts: &[AccountInfo]) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let authority = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if !authority.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let mut lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if authority.key != &lysergic_tokenizer_state.authority {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		if lysergic_tokenizer_state.expiry_date >= clock::Clock::get()?.unix_timestamp {
+			return Err(TokenizerError::ExpiryDateNotElapsed.into());
+		}
+
+		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
+		}
+
+		if token_program.key != &lysergic_tokenizer_state.token_program {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if lysergic_tokenizer_state.untokenized_principal != 0 {
+			return Err(TokenizerError::TokenizerHasDeposits.into());
+		}
+
+		Self::revoke_mint_authority(
+			lysergic_tokenizer_account,
+			&lysergic_tokenizer_state,
+			principal_token_mint_account,
+			token_program,
+		)?;
+
+		lysergic_tokenizer_state.principal_mint_terminated = true;
+		lysergic_tokenizer_state
+			.serialize(&mut &mut lysergic_tokenizer_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	fn process_initialize_amm(accounts: &[AccountInfo], fee_bps: u16) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let pool_account = next_account_info(account_info_iter)?;
+		let lysergic_tokenizer_account = next_account_info(account_info_iter)?;
+		let authority = next_account_info(account_info_iter)?;
+		let lp_mint_account = next_account_info(account_info_iter)?;
+		let pool_principal_token_account = next_account_info(account_info_iter)?;
+		let pool_underlying_token_account = next_account_info(account_info_iter)?;
+		let principal_token_mint_account = next_account_info(account_info_iter)?;
+		let underlying_mint_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+
+		if fee_bps > MAX_POOL_FEE_BPS {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		if lysergic_tokenizer_account.owner != &crate::id() {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if !authority.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let lysergic_tokenizer_state =
+			TokenizerState::unpack_initialized(&lysergic_tokenizer_account.data.borrow()[..])?;
+
+		if authority.key != &lysergic_tokenizer_state.authority {
+			return Err(TokenizerError::Unauthorised.into());
+		}
+
+		// The pool trades principal, so its mint has to exist
+		if !lysergic_tokenizer_state.mints_initialized {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		if principal_token_mint_account.key != &lysergic_tokenizer_state.principal_token_mint {
+			return Err(TokenizerError::IncorrectPrincipalMintAddress.into());
+		}
+
+		if underlying_mint_account.key != &lysergic_tokenizer_state.underlying_mint {
+			return Err(TokenizerError::IncorrectUnderlyingMintAddress.into());
+		}
+
+		if token_program.key != &lysergic_tokenizer_state.token_program {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if system_program.key != &system_program::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		if atoken_program.key != &spl_associated_token_account::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		let (pool_key, bump) = get_pool_address(lysergic_tokenizer_account.key);
+		let (lp_mint_key, lp_bump) = get_lp_mint_address(&pool_key);
+
+		if pool_account.key != &pool_key {
+			return Err(TokenizerError::IncorrectPoolAddress.into());
+		}
+
+		if lp_mint_account.key != &lp_mint_key {
+			return Err(TokenizerError::IncorrectPoolAddress.into());
+		}
+
+		if pool_account.owner == &crate::id() {
+			return Err(ProgramError::AccountAlreadyInitialized);
+		}
+
+		if pool_principal_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				&pool_key,
+				principal_token_mint_account.key,
+			) || pool_underlying_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				&pool_key,
+				underlying_mint_account.key,
+			) {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		// LP tokens are minted in proportion to the reserves, so they share their decimals
+		let decimals = load_mint(underlying_mint_account)?.decimals;
+		let rent = rent::Rent::get()?;
+
+		msg!("Creating pool account");
+		invoke_signed(
+			&system_instruction::create_account(
+				authority.key,
+				&pool_key,
+				rent.minimum_balance(POOL_SIZE)
+					.max(1)
+					.saturating_sub(pool_account.lamports()),
+				POOL_SIZE as u64,
+				&crate::id(),
+			),
+			&[
+				authority.clone(),
+				pool_account.clone(),
+				system_program.clone(),
+			],
+			&[&[
+				b"pool",
+				&lysergic_tokenizer_account.key.to_bytes()[..],
+				&[bump],
+			]],
+		)?;
+
+		msg!("Creating LP token mint");
+		invoke_signed(
+			&system_instruction::create_account(
+				authority.key,
+				&lp_mint_key,
+				rent.minimum_balance(MINT_SIZE)
+					.max(1)
+					.saturating_sub(lp_mint_account.lamports()),
+				MINT_SIZE as u64,
+				token_program.key,
+			),
+			&[
+				authority.clone(),
+				lp_mint_account.clone(),
+				system_program.clone(),
+			],
+			&[&[b"lp", &pool_key.to_bytes()[..], &[lp_bump]]],
+		)?;
+
+		invoke(
+			&spl_token::instruction::initialize_mint2(
+				token_program.key,
+				&lp_mint_key,
+				&pool_key,
+				None,
+				decimals,
+			)?,
+			&[lp_mint_account.clone(), token_program.clone()],
+		)?;
+
+		msg!("Creating pool token accounts");
+		for (pool_token_account, mint_account) in [
+			(pool_principal_token_account, principal_token_mint_account),
+			(pool_underlying_token_account, underlying_mint_account),
+		] {
+			invoke(
+				&spl_associated_token_account::instruction::create_associated_token_account(
+					authority.key,
+					&pool_key,
+					mint_account.key,
+					token_program.key,
+				),
+				&[
+					authority.clone(),
+					pool_token_account.clone(),
+					pool_account.clone(),
+					mint_account.clone(),
+					system_program.clone(),
+					token_program.clone(),
+					atoken_program.clone(),
+				],
+			)?;
+		}
+
+		let pool_state = PoolState {
+			is_initialized: true,
+			bump,
+			tokenizer: *lysergic_tokenizer_account.key,
+			principal_token_mint: *principal_token_mint_account.key,
+			underlying_mint: *underlying_mint_account.key,
+			lp_mint: lp_mint_key,
+			fee_bps,
+			principal_reserve: 0,
+			underlying_reserve: 0,
+			token_program: *token_program.key,
+		};
+
+		pool_state.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	// Load a pool, checking it lives at its PDA and that the token accounts passed are its own
+	fn load_pool(
+		pool_account: &AccountInfo,
+		pool_principal_token_account: &AccountInfo,
+		pool_underlying_token_account: &AccountInfo,
+		token_program: &AccountInfo,
+	) -> Result<PoolState, ProgramError> {
+		if pool_account.owner != &crate::id() {
+			return Err(TokenizerError::PoolNotInitialized.into());
+		}
+
+		let pool_state = PoolState::unpack_initialized(&pool_account.data.borrow()[..])?;
+
+		let pool_key = Pubkey::create_program_address(
+			&[
+				b"pool",
+				&pool_state.tokenizer.to_bytes()[..],
+				&[pool_state.bump],
+			],
+			&crate::id(),
+		)
+		.map_err(|_| TokenizerError::IncorrectPoolAddress)?;
+
+		if pool_account.key != &pool_key {
+			return Err(TokenizerError::IncorrectPoolAddress.into());
+		}
+
+		if pool_principal_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				&pool_key,
+				&pool_state.principal_token_mint,
+			) || pool_underlying_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				&pool_key,
+				&pool_state.underlying_mint,
+			) {
+			return Err(TokenizerError::IncorrectVaultAddress.into());
+		}
+
+		if token_program.key != &pool_state.token_program {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+
+		Ok(pool_state)
+	}
+
+	// Transfer tokens into the pool, signed by the user
+	fn transfer_to_pool<'a>(
+		user_token_account: &AccountInfo<'a>,
+		pool_token_account: &AccountInfo<'a>,
+		user_account: &AccountInfo<'a>,
+		token_program: &AccountInfo<'a>,
+		amount: u64,
+	) -> ProgramResult {
+		invoke(
+			&spl_token::instruction::transfer(
+				token_program.key,
+				user_token_account.key,
+				pool_token_account.key,
+				user_account.key,
+				&[],
+				amount,
+			)?,
+			&[
+				user_token_account.clone(),
+				pool_token_account.clone(),
+				user_account.clone(),
+				token_program.clone(),
+			],
+		)
+	}
+
+	// Transfer tokens out of the pool, signed by the pool
+	fn transfer_from_pool<'a>(
+		pool_state: &PoolState,
+		pool_account: &AccountInfo<'a>,
+		pool_token_account: &AccountInfo<'a>,
+		user_token_account: &AccountInfo<'a>,
+		token_program: &AccountInfo<'a>,
+		amount: u64,
+	) -> ProgramResult {
+		invoke_signed(
+			&spl_token::instruction::transfer(
+				token_program.key,
+				pool_token_account.key,
+				user_token_account.key,
+				pool_account.key,
+				&[],
+				amount,
+			)?,
+			&[
+				pool_token_account.clone(),
+				user_token_account.clone(),
+				pool_account.clone(),
+				token_program.clone(),
+			],
+			&[&[
+				b"pool",
+				&pool_state.tokenizer.to_bytes()[..],
+				&[pool_state.bump],
+			]],
+		)
+	}
+
+	fn process_add_liquidity(
+		accounts: &[AccountInfo],
+		principal_amount: u64,
+		underlying_amount: u64,
+		min_lp_out: u64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let pool_account = next_account_info(account_info_iter)?;
+		let pool_principal_token_account = next_account_info(account_info_iter)?;
+		let pool_underlying_token_account = next_account_info(account_info_iter)?;
+		let lp_mint_account = next_account_info(account_info_iter)?;
+		let user_account = next_account_info(account_info_iter)?;
+		let user_principal_token_account = next_account_info(account_info_iter)?;
+		let user_underlying_token_account = next_account_info(account_info_iter)?;
+		let user_lp_token_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+		let system_program = next_account_info(account_info_iter)?;
+		let atoken_program = next_account_info(account_info_iter)?;
+
+		let mut pool_state = Self::load_pool(
+			pool_account,
+			pool_principal_token_account,
+			pool_underlying_token_account,
+			token_program,
+		)?;
+
+		if !user_account.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		if lp_mint_account.key != &pool_state.lp_mint {
+			return Err(TokenizerError::IncorrectPoolAddress.into());
+		}
+
+		if user_lp_token_account.key
+			!= &spl_associated_token_account::get_associated_token_address(
+				user_account.key,
+				&pool_state.lp_mint,
+			) {
+			return Err(TokenizerError::InvalidUserAccount.into());
+		}
+
+		let (lp_amount, principal_in, underlying_in) = deposit_amounts(
+			principal_amount,
+			underlying_amount,
+			pool_state.principal_reserve,
+			pool_state.underlying_reserve,
+			load_mint(lp_mint_account)?.supply,
+		)
+		.ok_or(ProgramError::ArithmeticOverflow)?;
+
+		if lp_amount == 0 {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		if lp_amount < min_lp_out {
+			return Err(TokenizerError::SlippageExceeded.into());
+		}
+
+		if user_lp_token_account.owner != token_program.key {
+			msg!("No user LP token account found, creating...");
+			Self::create_user_token_account(
+				lp_mint_account,
+				user_account,
+				user_lp_token_account,
+				token_program,
+				system_program,
+				atoken_program,
+			)?;
+		}
+
+		Self::transfer_to_pool(
+			user_principal_token_account,
+			pool_principal_token_account,
+			user_account,
+			token_program,
+			principal_in,
+		)?;
+		Self::transfer_to_pool(
+			user_underlying_token_account,
+			pool_underlying_token_account,
+			user_account,
+			token_program,
+			underlying_in,
+		)?;
+
+		msg!("Minting {} LP tokens to user...", lp_amount);
+		invoke_signed(
+			&spl_token::instruction::mint_to(
+				token_program.key,
+				lp_mint_account.key,
+				user_lp_token_account.key,
+				pool_account.key,
+				&[],
+				lp_amount,
+			)?,
+			&[
+				lp_mint_account.clone(),
+				user_lp_token_account.clone(),
+				pool_account.clone(),
+				token_program.clone(),
+			],
+			&[&[
+				b"pool",
+				&pool_state.tokenizer.to_bytes()[..],
+				&[pool_state.bump],
+			]],
+		)?;
+
+		pool_state.principal_reserve = pool_state
+			.principal_reserve
+			.checked_add(principal_in)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+		pool_state.underlying_reserve = pool_state
+			.underlying_reserve
+			.checked_add(underlying_in)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+		pool_state.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	fn process_remove_liquidity(
+		accounts: &[AccountInfo],
+		lp_amount: u64,
+		min_principal_out: u64,
+		min_underlying_out: u64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let pool_account = next_account_info(account_info_iter)?;
+		let pool_principal_token_account = next_account_info(account_info_iter)?;
+		let pool_underlying_token_account = next_account_info(account_info_iter)?;
+		let lp_mint_account = next_account_info(account_info_iter)?;
+		let user_account = next_account_info(account_info_iter)?;
+		let user_principal_token_account = next_account_info(account_info_iter)?;
+		let user_underlying_token_account = next_account_info(account_info_iter)?;
+		let user_lp_token_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+
+		let mut pool_state = Self::load_pool(
+			pool_account,
+			pool_principal_token_account,
+			pool_underlying_token_account,
+			token_program,
+		)?;
+
+		if !user_account.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		if lp_mint_account.key != &pool_state.lp_mint {
+			return Err(TokenizerError::IncorrectPoolAddress.into());
+		}
+
+		let (principal_out, underlying_out) = amounts_for_withdrawal(
+			lp_amount,
+			pool_state.principal_reserve,
+			pool_state.underlying_reserve,
+			load_mint(lp_mint_account)?.supply,
+		)
+		.ok_or(ProgramError::ArithmeticOverflow)?;
+
+		if principal_out < min_principal_out || underlying_out < min_underlying_out {
+			return Err(TokenizerError::SlippageExceeded.into());
+		}
+
+		invoke(
+			&spl_token::instruction::burn(
+				token_program.key,
+				user_lp_token_account.key,
+				lp_mint_account.key,
+				user_account.key,
+				&[],
+				lp_amount,
+			)?,
+			&[
+				user_lp_token_account.clone(),
+				lp_mint_account.clone(),
+				user_account.clone(),
+				token_program.clone(),
+			],
+		)?;
+
+		Self::transfer_from_pool(
+			&pool_state,
+			pool_account,
+			pool_principal_token_account,
+			user_principal_token_account,
+			token_program,
+			principal_out,
+		)?;
+		Self::transfer_from_pool(
+			&pool_state,
+			pool_account,
+			pool_underlying_token_account,
+			user_underlying_token_account,
+			token_program,
+			underlying_out,
+		)?;
+
+		pool_state.principal_reserve = pool_state
+			.principal_reserve
+			.checked_sub(principal_out)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+		pool_state.underlying_reserve = pool_state
+			.underlying_reserve
+			.checked_sub(underlying_out)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+		pool_state.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
+
+		Ok(())
+	}
+
+	fn process_swap(
+		accounts: &[AccountInfo],
+		principal_in: bool,
+		amount_in: u64,
+		min_amount_out: u64,
+	) -> ProgramResult {
+		let account_info_iter = &mut accounts.iter();
+		let pool_account = next_account_info(account_info_iter)?;
+		let pool_principal_token_account = next_account_info(account_info_iter)?;
+		let pool_underlying_token_account = next_account_info(account_info_iter)?;
+		let user_account = next_account_info(account_info_iter)?;
+		let user_principal_token_account = next_account_info(account_info_iter)?;
+		let user_underlying_token_account = next_account_info(account_info_iter)?;
+		let token_program = next_account_info(account_info_iter)?;
+
+		let mut pool_state = Self::load_pool(
+			pool_account,
+			pool_principal_token_account,
+			pool_underlying_token_account,
+			token_program,
+		)?;
+
+		if !user_account.is_signer {
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+
+		let (reserve_in, reserve_out, pool_in, pool_out, user_in, user_out) = if principal_in {
+			(
+				pool_state.principal_reserve,
+				pool_state.underlying_reserve,
+				pool_principal_token_account,
+				pool_underlying_token_account,
+				user_principal_token_account,
+				user_underlying_token_account,
+			)
+		} else {
+			(
+				pool_state.underlying_reserve,
+				pool_state.principal_reserve,
+				pool_underlying_token_account,
+				pool_principal_token_account,
+				user_underlying_token_account,
+				user_principal_token_account,
+			)
+		};
+
+		let amount_out = swap_out(amount_in, reserve_in, reserve_out, pool_state.fee_bps)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
+		if amount_out == 0 {
+			return Err(ProgramError::InvalidArgument);
+		}
+
+		if amount_out < min_amount_out {
+			return Err(TokenizerError::SlippageExceeded.into());
+		}
+
+		Self::transfer_to_pool(user_in, pool_in, user_account, token_program, amount_in)?;
+		Self::transfer_from_pool(
+			&pool_state,
+			pool_account,
+			pool_out,
+			user_out,
+			token_program,
+			amount_out,
+		)?;
+
+		// The fee stays in the pool as part of the input reserve, accruing to LPs
+		let reserve_in = reserve_in
+			.checked_add(amount_in)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+		let reserve_out = reserve_out - amount_out;
+		if principal_in {
+			pool_state.principal_reserve = reserve_in;
+			pool_state.underlying_reserve = reserve_out;
+		} else {
+			pool_state.underlying_reserve = reserve_in;
+			pool_state.principal_reserve = reserve_out;
+		}
+		pool_state.serialize(&mut &mut pool_account.data.borrow_mut()[..])?;
+
 		Ok(())
 	}
 }
@@ -0,0 +1,176 @@
+use {
+	solana_program::{
+		account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+		program_option::COption, program_pack::Pack, pubkey::Pubkey,
+	},
+	spl_token::state::{Account, Mint},
+};
+
+// Tokenizers are only initialized under SPL Token, so every mint and token account they read
+// must belong to it
+fn check_token_program_owner(account: &AccountInfo) -> ProgramResult {
+	spl_token::check_program_account(account.owner).inspect_err(|_| {
+		msg!("Account {} is not owned by the token program", account.key);
+	})
+}
+
+// Unpacks an initialized mint, logging which account failed to unpack
+pub fn load_mint(mint_account: &AccountInfo) -> Result<Mint, ProgramError> {
+	check_token_program_owner(mint_account)?;
+
+	Mint::unpack(&mint_account.data.borrow()).inspect_err(|_| {
+		msg!("Account {} is not an initialized mint", mint_account.key);
+	})
+}
+
+// Unpacks an initialized token account, logging which account failed to unpack
+pub fn load_token_account(token_account: &AccountInfo) -> Result<Account, ProgramError> {
+	check_token_program_owner(token_account)?;
+
+	Account::unpack(&token_account.data.borrow()).inspect_err(|_| {
+		msg!(
+			"Account {} is not an initialized token account",
+			token_account.key
+		);
+	})
+}
+
+pub fn assert_mint_authority(mint: &Mint, expected: &Pubkey) -> ProgramResult {
+	if mint.mint_authority != COption::Some(*expected) {
+		msg!("Mint authority is not {}", expected);
+		return Err(ProgramError::InvalidAccountData);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use {super::*, spl_token::state::AccountState};
+
+	// Runs `f` with an account owned by `owner` holding `data`
+	fn with_account<R>(owner: Pubkey, mut data: Vec<u8>, f: impl FnOnce(&AccountInfo) -> R) -> R {
+		let key = Pubkey::new_unique();
+		let mut lamports = 1_000_000_000;
+		let account = AccountInfo::new(
+			&key,
+			false,
+			false,
+			&mut lamports,
+			&mut data,
+			&owner,
+			false,
+			0,
+		);
+
+		f(&account)
+	}
+
+	fn mint(authority: COption<Pubkey>) -> Mint {
+		Mint {
+			mint_authority: authority,
+			supply: 1_000,
+			decimals: 6,
+			is_initialized: true,
+			freeze_authority: COption::None,
+		}
+	}
+
+	fn packed<T: Pack>(value: T) -> Vec<u8> {
+		let mut data = vec![0; T::LEN];
+		T::pack(value, &mut data).unwrap();
+		data
+	}
+
+	#[test]
+	fn load_mint_unpacks_a_valid_mint() {
+		let expected = mint(COption::Some(Pubkey::new_unique()));
+		let loaded = with_account(spl_token::id(), packed(expected), load_mint);
+
+		assert_eq!(loaded, Ok(expected));
+	}
+
+	#[test]
+	fn load_mint_rejects_malformed_accounts() {
+		let data = packed(mint(COption::None));
+
+		assert_eq!(
+			with_account(spl_token::id(), data[..Mint::LEN - 1].to_vec(), load_mint),
+			Err(ProgramError::InvalidAccountData)
+		);
+		assert_eq!(
+			with_account(spl_token::id(), vec![0; Mint::LEN], load_mint),
+			Err(ProgramError::UninitializedAccount)
+		);
+		assert_eq!(
+			with_account(Pubkey::new_unique(), data, load_mint),
+			Err(ProgramError::IncorrectProgramId)
+		);
+	}
+
+	#[test]
+	fn load_token_account_unpacks_a_valid_account() {
+		let expected = Account {
+			mint: Pubkey::new_unique(),
+			owner: Pubkey::new_unique(),
+			amount: 1_000,
+			state: AccountState::Initialized,
+			..Account::default()
+		};
+		let loaded = with_account(spl_token::id(), packed(expected), load_token_account);
+
+		assert_eq!(loaded, Ok(expected));
+	}
+
+	#[test]
+	fn load_token_account_rejects_malformed_accounts() {
+		let data = packed(Account {
+			state: AccountState::Initialized,
+			..Account::default()
+		});
+
+		assert_eq!(
+			with_account(
+				spl_token::id(),
+				data[..Account::LEN - 1].to_vec(),
+				load_token_account
+			),
+			Err(ProgramError::InvalidAccountData)
+		);
+		assert_eq!(
+			with_account(spl_token::id(), vec![0; Account::LEN], load_token_account),
+			Err(ProgramError::UninitializedAccount)
+		);
+		// A mint is not a token account
+		assert_eq!(
+			with_account(
+				spl_token::id(),
+				packed(mint(COption::None)),
+				load_token_account
+			),
+			Err(ProgramError::InvalidAccountData)
+		);
+		assert_eq!(
+			with_account(Pubkey::new_unique(), data, load_token_account),
+			Err(ProgramError::IncorrectProgramId)
+		);
+	}
+
+	#[test]
+	fn assert_mint_authority_requires_the_expected_authority() {
+		let authority = Pubkey::new_unique();
+
+		assert_eq!(
+			assert_mint_authority(&mint(COption::Some(authority)), &authority),
+			Ok(())
+		);
+		assert_eq!(
+			assert_mint_authority(&mint(COption::Some(Pubkey::new_unique())), &authority),
+			Err(ProgramError::InvalidAccountData)
+		);
+		assert_eq!(
+			assert_mint_authority(&mint(COption::None), &authority),
+			Err(ProgramError::InvalidAccountData)
+		);
+	}
+}
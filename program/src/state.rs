@@ -1,18 +1,183 @@
 use {
+	crate::error::TokenizerError,
 	borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
-	solana_program::pubkey::Pubkey,
+	solana_program::{program_error::ProgramError, pubkey::Pubkey},
 };
 
-pub const STATE_SIZE: usize = 1 + 32 + 32 + 32 + 32 + 32 + 8 + 8; // 184 bytes
+pub const STATE_SIZE: usize =
+	1 + 1 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 8 + 1 + 1 + 1 + 8 + 8; // 246 bytes
+pub const CONFIG_SIZE: usize = 1 + 1 + 32 + 8 + 8; // 50 bytes
+pub const USER_POSITION_SIZE: usize = 1 + 1 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8; // 114 bytes
 
 #[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
 pub struct TokenizerState {
-    pub bump: u8,
+	pub is_initialized: bool,
+	pub bump: u8,
 	pub authority: Pubkey,
 	pub principal_token_mint: Pubkey,
 	pub yield_token_mint: Pubkey,
 	pub underlying_mint: Pubkey,
 	pub underlying_vault: Pubkey,
+	pub token_program: Pubkey,
 	pub expiry_date: i64,
 	pub fixed_apy: u64,
+	pub total_deposited: u64,
+	pub mints_terminated: bool,
+	// Seconds a user must wait after tokenizing before claiming yield, zero disables it
+	pub min_hold_seconds: i64,
+	pub mints_initialized: bool,
+	// The underlying's balance changes without transfers, so principal is redeemed for its
+	// share of the vault rather than 1:1
+	pub rebasing: bool,
+	// Set once the principal mint's authority has been revoked, before or by `mints_terminated`
+	pub principal_mint_terminated: bool,
+	// Underlying deposited through `DepositUnderlying` that is yet to be tokenized, summed over
+	// every user position
+	pub untokenized_principal: u64,
+	pub untokenized_yield: u64,
+}
+
+impl TokenizerState {
+	// Deserializes the state, rejecting accounts that were terminated or never initialized,
+	// e.g. an account recreated at a terminated tokenizer's address reads back as zeroes
+	pub fn unpack_initialized(data: &[u8]) -> Result<Self, ProgramError> {
+		let state = Self::try_from_slice(data)?;
+
+		if !state.is_initialized {
+			return Err(TokenizerError::TokenizerNotInitialized.into());
+		}
+
+		Ok(state)
+	}
+
+	// Principal the vault owes: the principal supply and the underlying deposited but not yet
+	// tokenized into principal
+	pub fn principal_outstanding(&self, principal_supply: u64) -> Result<u64, ProgramError> {
+		principal_supply
+			.checked_add(self.untokenized_principal)
+			.ok_or(ProgramError::ArithmeticOverflow)
+	}
+
+	pub fn yield_outstanding(&self, yield_supply: u64) -> Result<u64, ProgramError> {
+		yield_supply
+			.checked_add(self.untokenized_yield)
+			.ok_or(ProgramError::ArithmeticOverflow)
+	}
+}
+
+// Program-wide settings maintained by the admin, a single account at `get_program_config_address`
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+pub struct ProgramConfig {
+	pub is_initialized: bool,
+	pub bump: u8,
+	pub admin: Pubkey,
+	// Inclusive range of tenors, in months, new tokenizers may be created with
+	pub min_tenor: i64,
+	pub max_tenor: i64,
+}
+
+impl ProgramConfig {
+	pub fn unpack_initialized(data: &[u8]) -> Result<Self, ProgramError> {
+		let config = Self::try_from_slice(data)?;
+
+		if !config.is_initialized {
+			return Err(TokenizerError::ConfigNotInitialized.into());
+		}
+
+		Ok(config)
+	}
+
+	pub fn allows_tenor(&self, months: i64) -> bool {
+		(self.min_tenor..=self.max_tenor).contains(&months)
+	}
+}
+
+// A user's history with one tokenizer, at `get_user_position_address`. It is kept per user
+// rather than read from token balances so it is unaffected by principal or yield transfers.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+pub struct UserPosition {
+	pub is_initialized: bool,
+	pub bump: u8,
+	pub tokenizer: Pubkey,
+	pub user: Pubkey,
+	pub principal_minted: u64,
+	pub yield_claimed: u64,
+	// Principal-weighted average time of the user's deposits
+	pub deposit_timestamp: i64,
+	pub last_deposit_timestamp: i64,
+	// Underlying deposited through `DepositUnderlying` that the user may still tokenize, once
+	// into principal and once into yield
+	pub untokenized_principal: u64,
+	pub untokenized_yield: u64,
+}
+
+impl UserPosition {
+	pub fn unpack_initialized(data: &[u8]) -> Result<Self, ProgramError> {
+		let position = Self::try_from_slice(data)?;
+
+		if !position.is_initialized {
+			return Err(ProgramError::UninitializedAccount);
+		}
+
+		Ok(position)
+	}
+
+	// Adds newly minted principal, moving the deposit timestamp towards `now` by its weight
+	pub fn record_principal(&mut self, principal: u64, now: i64) -> Result<(), ProgramError> {
+		let total = self
+			.principal_minted
+			.checked_add(principal)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
+		if total != 0 {
+			let weighted = self.deposit_timestamp as i128 * self.principal_minted as i128
+				+ now as i128 * principal as i128;
+			self.deposit_timestamp = (weighted / total as i128) as i64;
+		}
+		self.principal_minted = total;
+		self.last_deposit_timestamp = now;
+
+		Ok(())
+	}
+
+	pub fn record_deposit(&mut self, amount: u64) -> Result<(), ProgramError> {
+		self.untokenized_principal = self
+			.untokenized_principal
+			.checked_add(amount)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+		self.untokenized_yield = self
+			.untokenized_yield
+			.checked_add(amount)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
+		Ok(())
+	}
+
+	// Tokenizing can never mint more than the user deposited and has not yet tokenized
+	pub fn take_untokenized_principal(&mut self, amount: u64) -> Result<(), ProgramError> {
+		self.untokenized_principal = self
+			.untokenized_principal
+			.checked_sub(amount)
+			.ok_or(TokenizerError::InsufficientFunds)?;
+
+		Ok(())
+	}
+
+	pub fn take_untokenized_yield(&mut self, amount: u64) -> Result<(), ProgramError> {
+		self.untokenized_yield = self
+			.untokenized_yield
+			.checked_sub(amount)
+			.ok_or(TokenizerError::InsufficientFunds)?;
+
+		Ok(())
+	}
+
+	pub fn record_yield_claimed(&mut self, amount: u64) -> Result<(), ProgramError> {
+		self.yield_claimed = self
+			.yield_claimed
+			.checked_add(amount)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
+		Ok(())
+	}
 }
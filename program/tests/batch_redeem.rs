@@ -0,0 +1,90 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{Expiry, MAX_BATCH_REDEEM_ENTRIES},
+	solana_sdk::{
+		instruction::InstructionError,
+		message::Message,
+		packet::PACKET_DATA_SIZE,
+		pubkey::Pubkey,
+		signature::{Keypair, Signer},
+	},
+};
+
+#[test]
+fn full_batch_fits_in_a_transaction() {
+	let tokenizer = Tokenizer::new(Pubkey::new_unique(), Pubkey::new_unique(), START);
+	let entries: Vec<_> = (0..MAX_BATCH_REDEEM_ENTRIES)
+		.map(|_| (Pubkey::new_unique(), 1, 1))
+		.collect();
+
+	let message = Message::new(
+		&[tokenizer.batch_redeem(&entries)],
+		Some(&Pubkey::new_unique()),
+	);
+	// A compact-u16 signature count followed by the signatures and the message
+	let size = 1 + 64 * message.header.num_required_signatures as usize + message.serialize().len();
+	assert!(size <= PACKET_DATA_SIZE, "{} bytes", size);
+}
+
+#[tokio::test]
+async fn batch_redeem_three_positions() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let mut users = Vec::new();
+
+	for amount in [100, 200, 300] {
+		let user = create_user(&mut context, &tokenizer.underlying_mint, amount).await;
+		send(
+			&mut context,
+			&[tokenizer.deposit_and_tokenize(&user.pubkey(), amount)],
+			&[&user],
+		)
+		.await
+		.unwrap();
+		users.push((user, amount));
+	}
+	tokenizer.accrue(&mut context, 60).await;
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+
+	// Each user redeems all their principal and claims yield in proportion to their deposit
+	let entries: Vec<_> = users
+		.iter()
+		.map(|(user, amount)| (user.pubkey(), *amount, amount / 10))
+		.collect();
+	let signers: Vec<&Keypair> = users.iter().map(|(user, _)| user).collect();
+	send(&mut context, &[tokenizer.batch_redeem(&entries)], &signers)
+		.await
+		.unwrap();
+
+	for (user, amount) in &users {
+		assert_eq!(
+			tokenizer
+				.underlying_balance(&mut context, &user.pubkey())
+				.await,
+			amount + amount / 10
+		);
+		assert_eq!(
+			tokenizer
+				.principal_balance(&mut context, &user.pubkey())
+				.await,
+			0
+		);
+	}
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 0);
+}
+
+#[tokio::test]
+async fn batch_redeem_rejects_too_many_entries() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 0).await;
+	let entries = vec![(user.pubkey(), 1, 1); MAX_BATCH_REDEEM_ENTRIES + 1];
+
+	let result = send(&mut context, &[tokenizer.batch_redeem(&entries)], &[&user]).await;
+	assert_error(result, InstructionError::InvalidInstructionData);
+}
@@ -0,0 +1,57 @@
+// A frozen build rejects most instructions before reading their accounts
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::{stub::*, *},
+	solana_sdk::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey},
+};
+
+// Runs `instruction` with the tokenizer's state and the user's principal and yield token
+// accounts in place, which `Unwind` reads before handing over to the pair redemption
+fn run(
+	tokenizer: &Tokenizer,
+	user: &Pubkey,
+	instruction: &Instruction,
+) -> Result<(), ProgramError> {
+	let mut accounts = StubAccount::for_instruction(instruction, |key| {
+		if key == &tokenizer.address {
+			return Some((sclr_token::id(), tokenizer_state(tokenizer)));
+		}
+
+		[tokenizer.principal_mint, tokenizer.yield_mint]
+			.into_iter()
+			.find(|mint| key == &ata(user, mint))
+			.map(|mint| (spl_token::id(), token_account(&mint, user, 1_000)))
+	});
+	process(instruction, &mut accounts)
+}
+
+// Each builder passes every account its handler reads, and no more: the handler fails on the
+// stubs' state rather than running out of accounts, until the last meta is dropped
+#[test]
+fn builder_metas_satisfy_the_processor() {
+	set_clock(START);
+	let tokenizer = Tokenizer::new(
+		Pubkey::new_unique(),
+		Pubkey::new_unique(),
+		START + 365 * DAY,
+	);
+	let user = Pubkey::new_unique();
+
+	for (name, mut instruction) in builders(&tokenizer, user) {
+		assert_ne!(
+			run(&tokenizer, &user, &instruction),
+			Err(ProgramError::NotEnoughAccountKeys),
+			"{name}"
+		);
+
+		instruction.accounts.pop();
+		assert_eq!(
+			run(&tokenizer, &user, &instruction),
+			Err(ProgramError::NotEnoughAccountKeys),
+			"{name} without its last account"
+		);
+	}
+}
@@ -0,0 +1,135 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{error::TokenizerError, instruction::RedeemOrder, Expiry},
+	solana_sdk::signature::Signer,
+};
+
+#[tokio::test]
+async fn claim_is_capped_to_the_surplus() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+	let address = user.pubkey();
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&address, 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	tokenizer.accrue(&mut context, 50).await;
+
+	let result = send(
+		&mut context,
+		&[tokenizer.claim_yield(&address, 100, true)],
+		&[&user],
+	)
+	.await;
+	assert_error(result, tokenizer_error(TokenizerError::InsufficientFunds));
+
+	send(
+		&mut context,
+		&[tokenizer.claim_yield(&address, 100, false)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	// Only the yield tokens actually paid out are burned
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		50
+	);
+	assert_eq!(tokenizer.yield_balance(&mut context, &address).await, 950);
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 1_000);
+}
+
+#[tokio::test]
+async fn pair_redemption_burns_all_yield_without_surplus() {
+	for redeem_order in [RedeemOrder::PrincipalFirst, RedeemOrder::YieldFirst] {
+		let mut context = start().await;
+		let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+		let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+		let address = user.pubkey();
+
+		send(
+			&mut context,
+			&[tokenizer.deposit_and_tokenize(&address, 1_000)],
+			&[&user],
+		)
+		.await
+		.unwrap();
+
+		send(
+			&mut context,
+			&[tokenizer.redeem_principal_and_yield(&address, 400, 400, redeem_order)],
+			&[&user],
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(
+			tokenizer.underlying_balance(&mut context, &address).await,
+			400
+		);
+		assert_eq!(
+			tokenizer.principal_balance(&mut context, &address).await,
+			600
+		);
+		assert_eq!(tokenizer.yield_balance(&mut context, &address).await, 600);
+		assert_eq!(mint(&mut context, &tokenizer.yield_mint).await.supply, 600);
+	}
+}
+
+#[tokio::test]
+async fn pair_redemption_of_the_full_position_checks_the_minimum() {
+	for (min_total_underlying_out, expected) in [
+		(1_051, Err(TokenizerError::SlippageExceeded)),
+		(1_050, Ok(())),
+	] {
+		let mut context = start().await;
+		let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+		let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+		let address = user.pubkey();
+
+		send(
+			&mut context,
+			&[tokenizer.deposit_and_tokenize(&address, 1_000)],
+			&[&user],
+		)
+		.await
+		.unwrap();
+		tokenizer.accrue(&mut context, 50).await;
+
+		// 1,000 of principal plus the 50 surplus
+		let result = send(
+			&mut context,
+			&[tokenizer.redeem_principal_and_yield(
+				&address,
+				1_000,
+				min_total_underlying_out,
+				RedeemOrder::PrincipalFirst,
+			)],
+			&[&user],
+		)
+		.await;
+
+		match expected {
+			Ok(()) => {
+				result.unwrap();
+				assert_eq!(
+					tokenizer.underlying_balance(&mut context, &address).await,
+					1_050
+				);
+				assert_eq!(tokenizer.vault_balance(&mut context).await, 0);
+			}
+			Err(error) => assert_error(result, tokenizer_error(error)),
+		}
+	}
+}
@@ -0,0 +1,788 @@
+// Shared ProgramTest harness. Every test binary only uses part of it.
+#![allow(dead_code)]
+
+use {
+	borsh::BorshDeserialize,
+	sclr_token::{
+		error::TokenizerError,
+		get_principal_mint_address, get_yield_mint_address,
+		instruction::{self, RedeemOrder, TokenizerConfig},
+		state::{TokenizerState, UserPosition},
+		Expiry,
+	},
+	solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext},
+	solana_sdk::{
+		account::{Account, AccountSharedData},
+		bpf_loader_upgradeable,
+		clock::Clock,
+		instruction::{Instruction, InstructionError},
+		program_option::COption,
+		program_pack::Pack,
+		pubkey::Pubkey,
+		signature::{Keypair, Signer},
+		system_instruction,
+		transaction::{Transaction, TransactionError},
+	},
+	spl_token::state::{Account as TokenAccount, Mint},
+};
+
+pub mod stub;
+
+pub const DAY: i64 = 24 * 60 * 60;
+// Midday on 2024-01-01 UTC, the clock every test starts at
+pub const START: i64 = 1_704_067_200 + DAY / 2;
+pub const DECIMALS: u8 = 6;
+
+// Starts the program with the payer as its upgrade authority, the clock at `START` and a
+// program config allowing every supported tenor
+pub async fn start() -> ProgramTestContext {
+	let mut context = start_without_config().await;
+	let payer = context.payer.pubkey();
+
+	send(
+		&mut context,
+		&[instruction::init_config(&payer, 1, sclr_token::MAX_TENOR_MONTHS).unwrap()],
+		&[],
+	)
+	.await
+	.unwrap();
+
+	context
+}
+
+pub async fn start_without_config() -> ProgramTestContext {
+	let program_test = ProgramTest::new(
+		"sclr_token",
+		sclr_token::id(),
+		processor!(sclr_token::entrypoint::process_instruction),
+	);
+	let mut context = program_test.start_with_context().await;
+
+	// The program is loaded as a builtin, so fake the program data account the upgradeable
+	// loader would have created, naming the payer as upgrade authority
+	let mut data = vec![0; 45];
+	data[..4].copy_from_slice(&3u32.to_le_bytes());
+	data[12] = 1;
+	data[13..45].copy_from_slice(&context.payer.pubkey().to_bytes());
+	context.set_account(
+		&bpf_loader_upgradeable::get_program_data_address(&sclr_token::id()),
+		&AccountSharedData::from(Account {
+			lamports: 1_000_000_000,
+			data,
+			owner: bpf_loader_upgradeable::id(),
+			executable: false,
+			rent_epoch: 0,
+		}),
+	);
+
+	warp_to(&mut context, START).await;
+	context
+}
+
+pub async fn warp_to(context: &mut ProgramTestContext, unix_timestamp: i64) {
+	let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+	clock.unix_timestamp = unix_timestamp;
+	context.set_sysvar(&clock);
+}
+
+// Sends the instructions in one transaction paid for by the payer, under a fresh blockhash so
+// repeating an identical transaction is not deduplicated
+pub async fn send(
+	context: &mut ProgramTestContext,
+	instructions: &[Instruction],
+	signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+	let blockhash = context.get_new_latest_blockhash().await.unwrap();
+	let mut all_signers = vec![&context.payer];
+	all_signers.extend_from_slice(signers);
+
+	let transaction = Transaction::new_signed_with_payer(
+		instructions,
+		Some(&context.payer.pubkey()),
+		&all_signers,
+		blockhash,
+	);
+
+	context.banks_client.process_transaction(transaction).await
+}
+
+pub fn tokenizer_error(error: TokenizerError) -> InstructionError {
+	InstructionError::Custom(error as u32)
+}
+
+#[track_caller]
+pub fn assert_error(result: Result<(), BanksClientError>, expected: InstructionError) {
+	match result.unwrap_err().unwrap() {
+		TransactionError::InstructionError(_, error) => assert_eq!(error, expected),
+		error => panic!("unexpected transaction error {:?}", error),
+	}
+}
+
+pub async fn create_mint(context: &mut ProgramTestContext) -> Pubkey {
+	let mint = Keypair::new();
+	let payer = context.payer.pubkey();
+	let rent = context.banks_client.get_rent().await.unwrap();
+
+	send(
+		context,
+		&[
+			system_instruction::create_account(
+				&payer,
+				&mint.pubkey(),
+				rent.minimum_balance(Mint::LEN),
+				Mint::LEN as u64,
+				&spl_token::id(),
+			),
+			spl_token::instruction::initialize_mint2(
+				&spl_token::id(),
+				&mint.pubkey(),
+				&payer,
+				None,
+				DECIMALS,
+			)
+			.unwrap(),
+		],
+		&[&mint],
+	)
+	.await
+	.unwrap();
+
+	mint.pubkey()
+}
+
+// Mints `amount` of a mint the payer controls to an owner's associated token account, creating
+// it if needed
+pub async fn mint_to(context: &mut ProgramTestContext, mint: &Pubkey, owner: &Pubkey, amount: u64) {
+	let payer = context.payer.pubkey();
+	let token_account = spl_associated_token_account::get_associated_token_address(owner, mint);
+
+	send(
+		context,
+		&[
+			spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+				&payer,
+				owner,
+				mint,
+				&spl_token::id(),
+			),
+			spl_token::instruction::mint_to(
+				&spl_token::id(),
+				mint,
+				&token_account,
+				&payer,
+				&[],
+				amount,
+			)
+			.unwrap(),
+		],
+		&[],
+	)
+	.await
+	.unwrap();
+}
+
+// A funded user holding `amount` of the underlying
+pub async fn create_user(
+	context: &mut ProgramTestContext,
+	underlying_mint: &Pubkey,
+	amount: u64,
+) -> Keypair {
+	let user = Keypair::new();
+	let payer = context.payer.pubkey();
+
+	send(
+		context,
+		&[system_instruction::transfer(
+			&payer,
+			&user.pubkey(),
+			10_000_000_000,
+		)],
+		&[],
+	)
+	.await
+	.unwrap();
+	mint_to(context, underlying_mint, &user.pubkey(), amount).await;
+
+	user
+}
+
+pub async fn account(context: &mut ProgramTestContext, address: &Pubkey) -> Option<Account> {
+	context.banks_client.get_account(*address).await.unwrap()
+}
+
+pub async fn lamports(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+	account(context, address)
+		.await
+		.map_or(0, |account| account.lamports)
+}
+
+// Token balance of a token account, zero if it does not exist
+pub async fn balance(context: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+	account(context, token_account).await.map_or(0, |account| {
+		TokenAccount::unpack(&account.data).unwrap().amount
+	})
+}
+
+pub async fn mint(context: &mut ProgramTestContext, mint: &Pubkey) -> Mint {
+	Mint::unpack(&account(context, mint).await.unwrap().data).unwrap()
+}
+
+// Creates or overwrites an account, funded to be rent exempt
+pub async fn set_account_data(
+	context: &mut ProgramTestContext,
+	address: &Pubkey,
+	owner: Pubkey,
+	data: Vec<u8>,
+) {
+	let rent = context.banks_client.get_rent().await.unwrap();
+
+	context.set_account(
+		address,
+		&AccountSharedData::from(Account {
+			lamports: rent.minimum_balance(data.len()),
+			data,
+			owner,
+			executable: false,
+			rent_epoch: 0,
+		}),
+	);
+}
+
+pub fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+	spl_associated_token_account::get_associated_token_address(owner, mint)
+}
+
+// Addresses of a tokenizer created by the payer
+pub struct Tokenizer {
+	pub address: Pubkey,
+	pub authority: Pubkey,
+	pub underlying_mint: Pubkey,
+	pub vault: Pubkey,
+	pub principal_mint: Pubkey,
+	pub yield_mint: Pubkey,
+	pub expiry_date: i64,
+}
+
+impl Tokenizer {
+	pub fn new(authority: Pubkey, underlying_mint: Pubkey, expiry_date: i64) -> Self {
+		let (address, _) = sclr_token::get_tokenizer_address(&underlying_mint, expiry_date);
+
+		Self {
+			address,
+			authority,
+			underlying_mint,
+			vault: ata(&address, &underlying_mint),
+			principal_mint: get_principal_mint_address(&address).0,
+			yield_mint: get_yield_mint_address(&address).0,
+			expiry_date,
+		}
+	}
+
+	// Creates a new underlying mint and a tokenizer with its mints for it, starting now
+	pub async fn create(context: &mut ProgramTestContext, expiry: Expiry, fixed_apy: u64) -> Self {
+		let underlying_mint = create_mint(context).await;
+		Self::create_for(context, underlying_mint, expiry, fixed_apy).await
+	}
+
+	pub async fn create_for(
+		context: &mut ProgramTestContext,
+		underlying_mint: Pubkey,
+		expiry: Expiry,
+		fixed_apy: u64,
+	) -> Self {
+		let now = context
+			.banks_client
+			.get_sysvar::<Clock>()
+			.await
+			.unwrap()
+			.unix_timestamp;
+		let authority = context.payer.pubkey();
+		let instructions = TokenizerConfig::new(authority, underlying_mint, expiry.clone())
+			.fixed_apy(fixed_apy)
+			.instructions(now)
+			.unwrap();
+
+		send(context, &instructions, &[]).await.unwrap();
+
+		Self::new(
+			authority,
+			underlying_mint,
+			expiry.to_expiry_date(now).unwrap(),
+		)
+	}
+
+	// Writes a tokenizer straight into the bank, as a frozen build cannot create one: `user`
+	// holds `amount` of principal and yield, backed by `amount` plus `surplus` in the vault
+	pub async fn install(
+		context: &mut ProgramTestContext,
+		underlying_mint: Pubkey,
+		expiry_date: i64,
+		user: &Pubkey,
+		amount: u64,
+		surplus: u64,
+	) -> Self {
+		let tokenizer = Self::new(context.payer.pubkey(), underlying_mint, expiry_date);
+
+		let mut state = TokenizerState::try_from_slice(&stub::tokenizer_state(&tokenizer)).unwrap();
+		state.total_deposited = amount;
+		set_account_data(
+			context,
+			&tokenizer.address,
+			sclr_token::id(),
+			borsh::to_vec(&state).unwrap(),
+		)
+		.await;
+		set_account_data(
+			context,
+			&tokenizer.vault,
+			spl_token::id(),
+			stub::token_account(&underlying_mint, &tokenizer.address, amount + surplus),
+		)
+		.await;
+
+		for mint in [tokenizer.principal_mint, tokenizer.yield_mint] {
+			let mut data = vec![0; Mint::LEN];
+			Mint::pack(
+				Mint {
+					mint_authority: COption::Some(tokenizer.address),
+					supply: amount,
+					decimals: DECIMALS,
+					is_initialized: true,
+					freeze_authority: COption::None,
+				},
+				&mut data,
+			)
+			.unwrap();
+			set_account_data(context, &mint, spl_token::id(), data).await;
+			set_account_data(
+				context,
+				&ata(user, &mint),
+				spl_token::id(),
+				stub::token_account(&mint, user, amount),
+			)
+			.await;
+		}
+
+		tokenizer
+	}
+
+	pub async fn state(&self, context: &mut ProgramTestContext) -> TokenizerState {
+		TokenizerState::try_from_slice(&account(context, &self.address).await.unwrap().data)
+			.unwrap()
+	}
+
+	pub async fn position(&self, context: &mut ProgramTestContext, user: &Pubkey) -> UserPosition {
+		let (address, _) = sclr_token::get_user_position_address(&self.address, user);
+		UserPosition::try_from_slice(&account(context, &address).await.unwrap().data).unwrap()
+	}
+
+	pub async fn underlying_balance(
+		&self,
+		context: &mut ProgramTestContext,
+		owner: &Pubkey,
+	) -> u64 {
+		balance(context, &ata(owner, &self.underlying_mint)).await
+	}
+
+	pub async fn principal_balance(&self, context: &mut ProgramTestContext, owner: &Pubkey) -> u64 {
+		balance(context, &ata(owner, &self.principal_mint)).await
+	}
+
+	pub async fn yield_balance(&self, context: &mut ProgramTestContext, owner: &Pubkey) -> u64 {
+		balance(context, &ata(owner, &self.yield_mint)).await
+	}
+
+	pub async fn vault_balance(&self, context: &mut ProgramTestContext) -> u64 {
+		balance(context, &self.vault).await
+	}
+
+	// Mints underlying straight into the vault, standing in for the yield the underlying
+	// earns, or for a rebase up
+	pub async fn accrue(&self, context: &mut ProgramTestContext, amount: u64) {
+		let payer = context.payer.pubkey();
+
+		send(
+			context,
+			&[spl_token::instruction::mint_to(
+				&spl_token::id(),
+				&self.underlying_mint,
+				&self.vault,
+				&payer,
+				&[],
+				amount,
+			)
+			.unwrap()],
+			&[],
+		)
+		.await
+		.unwrap();
+	}
+
+	// Overwrites the vault's balance without any instruction, standing in for a rebase down or
+	// for underlying lost to an accounting bug
+	pub async fn set_vault_balance(&self, context: &mut ProgramTestContext, amount: u64) {
+		let mut vault = account(context, &self.vault).await.unwrap();
+		let mut token_account = TokenAccount::unpack(&vault.data).unwrap();
+		token_account.amount = amount;
+		TokenAccount::pack(token_account, &mut vault.data).unwrap();
+		context.set_account(&self.vault, &AccountSharedData::from(vault));
+	}
+
+	pub fn deposit_underlying(&self, user: &Pubkey, amount: u64) -> Instruction {
+		instruction::deposit_underlying(
+			&self.address,
+			&self.vault,
+			&self.principal_mint,
+			&self.yield_mint,
+			user,
+			&ata(user, &self.underlying_mint),
+			amount,
+		)
+		.unwrap()
+	}
+
+	pub fn tokenize_principal(&self, user: &Pubkey, amount: u64) -> Instruction {
+		instruction::tokenize_principal(
+			&self.address,
+			&self.vault,
+			&self.principal_mint,
+			&self.yield_mint,
+			user,
+			&ata(user, &self.principal_mint),
+			amount,
+		)
+		.unwrap()
+	}
+
+	pub fn tokenize_yield(&self, user: &Pubkey, amount: u64) -> Instruction {
+		instruction::tokenize_yield(
+			&self.address,
+			&self.vault,
+			&self.principal_mint,
+			&self.yield_mint,
+			user,
+			&ata(user, &self.yield_mint),
+			amount,
+		)
+		.unwrap()
+	}
+
+	pub fn deposit_and_tokenize(&self, user: &Pubkey, amount: u64) -> Instruction {
+		instruction::deposit_and_tokenize(
+			&self.address,
+			&self.vault,
+			&self.principal_mint,
+			&self.yield_mint,
+			user,
+			&ata(user, &self.underlying_mint),
+			&ata(user, &self.principal_mint),
+			&ata(user, &self.yield_mint),
+			amount,
+		)
+		.unwrap()
+	}
+
+	pub fn redeem_mature_principal(&self, user: &Pubkey, amount: u64) -> Instruction {
+		instruction::redeem_mature_principal(
+			&self.address,
+			&self.vault,
+			&self.underlying_mint,
+			&self.principal_mint,
+			&self.yield_mint,
+			user,
+			&ata(user, &self.underlying_mint),
+			&ata(user, &self.principal_mint),
+			amount,
+			false,
+			false,
+		)
+		.unwrap()
+	}
+
+	pub fn claim_yield(&self, user: &Pubkey, amount: u64, strict: bool) -> Instruction {
+		instruction::claim_yield(
+			&self.address,
+			&self.vault,
+			&self.underlying_mint,
+			&self.principal_mint,
+			&self.yield_mint,
+			user,
+			&ata(user, &self.underlying_mint),
+			&ata(user, &self.yield_mint),
+			amount,
+			strict,
+			false,
+		)
+		.unwrap()
+	}
+
+	// Yield tokens with nothing left to claim are burned by their holder through SPL Token
+	pub fn burn_yield(&self, user: &Pubkey, amount: u64) -> Instruction {
+		spl_token::instruction::burn(
+			&spl_token::id(),
+			&ata(user, &self.yield_mint),
+			&self.yield_mint,
+			user,
+			&[],
+			amount,
+		)
+		.unwrap()
+	}
+
+	pub fn unwind(
+		&self,
+		user: &Pubkey,
+		fraction_bps: u16,
+		min_total_underlying_out: u64,
+	) -> Instruction {
+		instruction::unwind(
+			&self.address,
+			&self.vault,
+			&self.underlying_mint,
+			&self.principal_mint,
+			&self.yield_mint,
+			user,
+			&ata(user, &self.underlying_mint),
+			&ata(user, &self.principal_mint),
+			&ata(user, &self.yield_mint),
+			fraction_bps,
+			min_total_underlying_out,
+		)
+		.unwrap()
+	}
+
+	pub fn batch_redeem(&self, entries: &[(Pubkey, u64, u64)]) -> Instruction {
+		instruction::batch_redeem(
+			&self.address,
+			&self.vault,
+			&self.underlying_mint,
+			&self.principal_mint,
+			&self.yield_mint,
+			entries,
+		)
+		.unwrap()
+	}
+
+	pub fn update_rebasing(&self, rebasing: bool) -> Instruction {
+		instruction::update_rebasing(
+			&self.address,
+			&self.authority,
+			&self.principal_mint,
+			&self.yield_mint,
+			rebasing,
+		)
+		.unwrap()
+	}
+
+	pub fn redeem_principal_and_yield(
+		&self,
+		user: &Pubkey,
+		amount: u64,
+		min_total_underlying_out: u64,
+		redeem_order: RedeemOrder,
+	) -> Instruction {
+		instruction::redeem_principal_and_yield(
+			&self.address,
+			&self.vault,
+			&self.underlying_mint,
+			&self.principal_mint,
+			&self.yield_mint,
+			user,
+			&ata(user, &self.underlying_mint),
+			&ata(user, &self.principal_mint),
+			&ata(user, &self.yield_mint),
+			amount,
+			min_total_underlying_out,
+			false,
+			redeem_order,
+		)
+		.unwrap()
+	}
+}
+
+// Every instruction builder, named, for `tokenizer` and `user`
+pub fn builders(tokenizer: &Tokenizer, user: Pubkey) -> Vec<(&'static str, Instruction)> {
+	let authority = tokenizer.authority;
+	let address = tokenizer.address;
+	let underlying_mint = tokenizer.underlying_mint;
+	let principal_mint = tokenizer.principal_mint;
+	let yield_mint = tokenizer.yield_mint;
+	let vault = tokenizer.vault;
+
+	vec![
+		(
+			"init_tokenizer",
+			instruction::init_tokenizer(
+				&address,
+				&authority,
+				&vault,
+				&underlying_mint,
+				&principal_mint,
+				&yield_mint,
+				Expiry::TwelveMonths,
+				tokenizer.expiry_date,
+				0,
+			)
+			.unwrap(),
+		),
+		(
+			"init_mints",
+			instruction::init_mints(
+				&address,
+				&authority,
+				&underlying_mint,
+				&principal_mint,
+				&yield_mint,
+				Expiry::TwelveMonths,
+				tokenizer.expiry_date,
+			)
+			.unwrap(),
+		),
+		(
+			"init_tokenizer_and_mints",
+			instruction::init_tokenizer_and_mints(
+				&address,
+				&authority,
+				&vault,
+				&underlying_mint,
+				&principal_mint,
+				&yield_mint,
+				Expiry::TwelveMonths,
+				tokenizer.expiry_date,
+				0,
+			)
+			.unwrap(),
+		),
+		(
+			"deposit_underlying",
+			tokenizer.deposit_underlying(&user, 100),
+		),
+		(
+			"tokenize_principal",
+			tokenizer.tokenize_principal(&user, 100),
+		),
+		("tokenize_yield", tokenizer.tokenize_yield(&user, 100)),
+		(
+			"deposit_and_tokenize",
+			tokenizer.deposit_and_tokenize(&user, 100),
+		),
+		(
+			"redeem_mature_principal",
+			tokenizer.redeem_mature_principal(&user, 100),
+		),
+		(
+			"redeem_principal_and_yield",
+			tokenizer.redeem_principal_and_yield(&user, 100, 0, RedeemOrder::PrincipalFirst),
+		),
+		("unwind", tokenizer.unwind(&user, 5_000, 0)),
+		("claim_yield", tokenizer.claim_yield(&user, 100, false)),
+		// Entries are read one at a time, the first failing before the next one's accounts
+		("batch_redeem", tokenizer.batch_redeem(&[(user, 100, 100)])),
+		("update_rebasing", tokenizer.update_rebasing(true)),
+		(
+			"terminate",
+			instruction::terminate(
+				&address,
+				&authority,
+				&vault,
+				&underlying_mint,
+				&principal_mint,
+				&yield_mint,
+			)
+			.unwrap(),
+		),
+		(
+			"terminate_tokenizer",
+			instruction::terminate_tokenizer(&address, &authority, &vault, &underlying_mint)
+				.unwrap(),
+		),
+		(
+			"terminate_mints",
+			instruction::terminate_mints(&address, &authority, &principal_mint, &yield_mint)
+				.unwrap(),
+		),
+		(
+			"terminate_principal_mint",
+			instruction::terminate_principal_mint(&address, &authority, &principal_mint).unwrap(),
+		),
+		(
+			"get_exchange_rate",
+			instruction::get_exchange_rate(&address).unwrap(),
+		),
+		(
+			"reconcile",
+			instruction::reconcile(&address, &vault, &principal_mint, &yield_mint).unwrap(),
+		),
+		(
+			"recreate_vault",
+			instruction::recreate_vault(&address, &authority, &vault, &underlying_mint).unwrap(),
+		),
+		(
+			"update_fixed_apy",
+			instruction::update_fixed_apy(&address, &authority, &principal_mint, &yield_mint, 500)
+				.unwrap(),
+		),
+		(
+			"update_min_hold",
+			instruction::update_min_hold(&address, &authority, DAY).unwrap(),
+		),
+		(
+			"init_config",
+			instruction::init_config(&authority, 1, 24).unwrap(),
+		),
+		(
+			"update_config",
+			instruction::update_config(&authority, 1, 24).unwrap(),
+		),
+		(
+			"initialize_amm",
+			instruction::initialize_amm(
+				&address,
+				&authority,
+				&principal_mint,
+				&underlying_mint,
+				30,
+			)
+			.unwrap(),
+		),
+		(
+			"add_liquidity",
+			instruction::add_liquidity(
+				&address,
+				&principal_mint,
+				&underlying_mint,
+				&user,
+				100,
+				100,
+				0,
+			)
+			.unwrap(),
+		),
+		(
+			"remove_liquidity",
+			instruction::remove_liquidity(
+				&address,
+				&principal_mint,
+				&underlying_mint,
+				&user,
+				100,
+				0,
+				0,
+			)
+			.unwrap(),
+		),
+		(
+			"swap",
+			instruction::swap(
+				&address,
+				&principal_mint,
+				&underlying_mint,
+				&user,
+				true,
+				100,
+				0,
+			)
+			.unwrap(),
+		),
+	]
+}
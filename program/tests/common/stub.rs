@@ -0,0 +1,144 @@
+// In-memory accounts for running the processor directly, without ProgramTest. Handlers run
+// this way only get as far as their checks, as no CPIs or sysvars other than the clock exist.
+
+use {
+	super::Tokenizer,
+	sclr_token::{get_tokenizer_address, processor::TokenizerProcessor, state::TokenizerState},
+	solana_sdk::{
+		account_info::AccountInfo,
+		clock::Clock,
+		entrypoint::{ProgramResult, SUCCESS},
+		instruction::Instruction,
+		program_pack::Pack,
+		program_stubs::{set_syscall_stubs, SyscallStubs},
+		pubkey::Pubkey,
+		system_program,
+	},
+	spl_token::state::{Account as TokenAccount, AccountState},
+};
+
+// The owned lamports and data an `AccountInfo` borrows
+pub struct StubAccount {
+	pub key: Pubkey,
+	pub owner: Pubkey,
+	pub lamports: u64,
+	pub data: Vec<u8>,
+	pub is_signer: bool,
+	pub is_writable: bool,
+}
+
+impl StubAccount {
+	pub fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+		Self {
+			key,
+			owner,
+			lamports: 1_000_000_000,
+			data,
+			is_signer: false,
+			is_writable: false,
+		}
+	}
+
+	// One stub per meta of `instruction`, with the owner and data `account` gives its key and
+	// an empty system account for keys it does not know
+	pub fn for_instruction(
+		instruction: &Instruction,
+		account: impl Fn(&Pubkey) -> Option<(Pubkey, Vec<u8>)>,
+	) -> Vec<Self> {
+		instruction
+			.accounts
+			.iter()
+			.map(|meta| {
+				let (owner, data) = account(&meta.pubkey).unwrap_or((system_program::id(), vec![]));
+				Self {
+					is_signer: meta.is_signer,
+					is_writable: meta.is_writable,
+					..Self::new(meta.pubkey, owner, data)
+				}
+			})
+			.collect()
+	}
+
+	pub fn info(&mut self) -> AccountInfo<'_> {
+		AccountInfo::new(
+			&self.key,
+			self.is_signer,
+			self.is_writable,
+			&mut self.lamports,
+			&mut self.data,
+			&self.owner,
+			false,
+			0,
+		)
+	}
+}
+
+pub fn process(instruction: &Instruction, accounts: &mut [StubAccount]) -> ProgramResult {
+	let infos: Vec<AccountInfo> = accounts.iter_mut().map(StubAccount::info).collect();
+	TokenizerProcessor::process(&instruction.program_id, &infos, &instruction.data)
+}
+
+struct ClockStub(i64);
+
+impl SyscallStubs for ClockStub {
+	fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+		// SAFETY: the runtime passes a pointer to a `Clock` to fill in
+		unsafe {
+			*(var_addr as *mut Clock) = Clock {
+				unix_timestamp: self.0,
+				..Clock::default()
+			};
+		}
+		SUCCESS
+	}
+}
+
+// Serves `Clock::get` at `unix_timestamp` for the rest of the test binary
+pub fn set_clock(unix_timestamp: i64) {
+	set_syscall_stubs(Box::new(ClockStub(unix_timestamp)));
+}
+
+// The state of `tokenizer` with its mints initialized and nothing deposited
+pub fn tokenizer_state(tokenizer: &Tokenizer) -> Vec<u8> {
+	let (_, bump) = get_tokenizer_address(&tokenizer.underlying_mint, tokenizer.expiry_date);
+	let state = TokenizerState {
+		is_initialized: true,
+		bump,
+		authority: tokenizer.authority,
+		principal_token_mint: tokenizer.principal_mint,
+		yield_token_mint: tokenizer.yield_mint,
+		underlying_mint: tokenizer.underlying_mint,
+		underlying_vault: tokenizer.vault,
+		token_program: spl_token::id(),
+		expiry_date: tokenizer.expiry_date,
+		fixed_apy: 0,
+		total_deposited: 0,
+		mints_terminated: false,
+		min_hold_seconds: 0,
+		mints_initialized: true,
+		rebasing: false,
+		principal_mint_terminated: false,
+		untokenized_principal: 0,
+		untokenized_yield: 0,
+	};
+
+	borsh::to_vec(&state).unwrap()
+}
+
+// An initialized token account of `mint` owned by `owner`
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+	let mut data = vec![0; TokenAccount::LEN];
+	TokenAccount::pack(
+		TokenAccount {
+			mint: *mint,
+			owner: *owner,
+			amount,
+			state: AccountState::Initialized,
+			..TokenAccount::default()
+		},
+		&mut data,
+	)
+	.unwrap();
+
+	data
+}
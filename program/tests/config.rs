@@ -0,0 +1,49 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{
+		error::TokenizerError,
+		instruction::{self, TokenizerConfig},
+		Expiry,
+	},
+	solana_sdk::signature::Signer,
+};
+
+#[tokio::test]
+async fn enable_a_tenor_then_init() {
+	let mut context = start_without_config().await;
+	let admin = context.payer.pubkey();
+	send(
+		&mut context,
+		&[instruction::init_config(&admin, 12, 12).unwrap()],
+		&[],
+	)
+	.await
+	.unwrap();
+
+	let underlying_mint = create_mint(&mut context).await;
+	let init = TokenizerConfig::new(admin, underlying_mint, Expiry::EighteenMonths)
+		.instructions(START)
+		.unwrap();
+	let result = send(&mut context, &init, &[]).await;
+	assert_error(result, tokenizer_error(TokenizerError::TenorNotEnabled));
+
+	send(
+		&mut context,
+		&[instruction::update_config(&admin, 12, 18).unwrap()],
+		&[],
+	)
+	.await
+	.unwrap();
+
+	let tokenizer =
+		Tokenizer::create_for(&mut context, underlying_mint, Expiry::EighteenMonths, 0).await;
+	assert_eq!(
+		tokenizer.state(&mut context).await.expiry_date,
+		Expiry::EighteenMonths.to_expiry_date(START).unwrap()
+	);
+}
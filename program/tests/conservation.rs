@@ -0,0 +1,77 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {common::*, proptest::prelude::*, sclr_token::Expiry, solana_sdk::signature::Signer};
+
+// Two users deposit, tokenize, let `accrued` of yield build up and redeem everything at
+// maturity. Returns what they got back in total and what is left in the vault.
+async fn lifecycle(deposits: [u64; 2], expiry: Expiry, accrued: u64) -> (u64, u64) {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, expiry, 0).await;
+	let mut users = Vec::new();
+
+	for amount in deposits {
+		let user = create_user(&mut context, &tokenizer.underlying_mint, amount).await;
+		send(
+			&mut context,
+			&[tokenizer.deposit_and_tokenize(&user.pubkey(), amount)],
+			&[&user],
+		)
+		.await
+		.unwrap();
+		users.push((user, amount));
+	}
+
+	tokenizer.accrue(&mut context, accrued).await;
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+
+	let mut redeemed = 0;
+	for (user, amount) in &users {
+		let address = user.pubkey();
+		send(
+			&mut context,
+			&[
+				tokenizer.redeem_mature_principal(&address, *amount),
+				tokenizer.claim_yield(&address, *amount, false),
+			],
+			&[user],
+		)
+		.await
+		.unwrap();
+		redeemed += tokenizer.underlying_balance(&mut context, &address).await;
+	}
+
+	(redeemed, tokenizer.vault_balance(&mut context).await)
+}
+
+proptest! {
+	// Every case boots a fresh ProgramTest, so keep the count low
+	#![proptest_config(ProptestConfig::with_cases(16))]
+
+	#[test]
+	fn lifecycle_conserves_underlying(
+		first in 1..=1_000_000_000_000u64,
+		second in 1..=1_000_000_000_000u64,
+		expiry in prop::sample::select(vec![
+			Expiry::TwelveMonths,
+			Expiry::EighteenMonths,
+			Expiry::TwentyFourMonths,
+		]),
+		accrued_bps in 0..=10_000u64,
+	) {
+		// At most the first deposit accrues, so the yield tokens can claim all of it
+		let accrued = first * accrued_bps / 10_000;
+		let (redeemed, vault) = tokio::runtime::Builder::new_current_thread()
+			.enable_all()
+			.build()
+			.unwrap()
+			.block_on(lifecycle([first, second], expiry, accrued));
+
+		// The rounding tolerance is zero: principal is minted and redeemed 1:1 and yield is paid
+		// from the vault's surplus without any proration, so underlying is conserved exactly
+		prop_assert_eq!(redeemed, first + second + accrued);
+		prop_assert_eq!(vault, 0);
+	}
+}
@@ -0,0 +1,78 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{
+		error::TokenizerError,
+		instruction::{self, RedeemOrder},
+		Expiry,
+	},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::signature::{Keypair, Signer},
+};
+
+// A tokenizer with a one day hold, holding one user's 1,000 deposit tokenized at `START` and
+// 100 of yield
+async fn held() -> (ProgramTestContext, Tokenizer, Keypair) {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+
+	send(
+		&mut context,
+		&[
+			instruction::update_min_hold(&tokenizer.address, &tokenizer.authority, DAY).unwrap(),
+			tokenizer.deposit_and_tokenize(&user.pubkey(), 1_000),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	tokenizer.accrue(&mut context, 100).await;
+
+	(context, tokenizer, user)
+}
+
+#[tokio::test]
+async fn claim_waits_for_the_hold() {
+	let (mut context, tokenizer, user) = held().await;
+	let claim = tokenizer.claim_yield(&user.pubkey(), 100, true);
+
+	warp_to(&mut context, START + DAY - 1).await;
+	let result = send(&mut context, std::slice::from_ref(&claim), &[&user]).await;
+	assert_error(result, tokenizer_error(TokenizerError::CooldownActive));
+
+	warp_to(&mut context, START + DAY).await;
+	send(&mut context, &[claim], &[&user]).await.unwrap();
+
+	assert_eq!(
+		tokenizer
+			.underlying_balance(&mut context, &user.pubkey())
+			.await,
+		100
+	);
+}
+
+#[tokio::test]
+async fn redeem_waits_for_the_hold() {
+	let (mut context, tokenizer, user) = held().await;
+	let redeem =
+		tokenizer.redeem_principal_and_yield(&user.pubkey(), 1_000, 0, RedeemOrder::PrincipalFirst);
+
+	warp_to(&mut context, START + DAY - 1).await;
+	let result = send(&mut context, std::slice::from_ref(&redeem), &[&user]).await;
+	assert_error(result, tokenizer_error(TokenizerError::CooldownActive));
+
+	warp_to(&mut context, START + DAY).await;
+	send(&mut context, &[redeem], &[&user]).await.unwrap();
+
+	assert_eq!(
+		tokenizer
+			.underlying_balance(&mut context, &user.pubkey())
+			.await,
+		1_100
+	);
+}
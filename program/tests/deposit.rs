@@ -0,0 +1,113 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{error::TokenizerError, instruction::TokenizerConfig, Expiry},
+	solana_sdk::signature::Signer,
+};
+
+#[tokio::test]
+async fn deposit_into_the_vault() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_underlying(&user.pubkey(), 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 1_000);
+	assert_eq!(
+		tokenizer
+			.underlying_balance(&mut context, &user.pubkey())
+			.await,
+		0
+	);
+}
+
+#[tokio::test]
+async fn deposit_rejects_an_unrelated_token_account_as_vault() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	// Another tokenizer of the same underlying, whose vault is a valid token account of the
+	// right mint
+	let other = Tokenizer::create_for(
+		&mut context,
+		tokenizer.underlying_mint,
+		Expiry::EighteenMonths,
+		0,
+	)
+	.await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+	let user_underlying = ata(&user.pubkey(), &tokenizer.underlying_mint);
+
+	for vault in [other.vault, user_underlying] {
+		let mut deposit = tokenizer.deposit_underlying(&user.pubkey(), 1_000);
+		deposit.accounts[1].pubkey = vault;
+
+		let result = send(&mut context, &[deposit], &[&user]).await;
+		assert_error(
+			result,
+			tokenizer_error(TokenizerError::IncorrectVaultAddress),
+		);
+	}
+
+	assert_eq!(
+		tokenizer
+			.underlying_balance(&mut context, &user.pubkey())
+			.await,
+		1_000
+	);
+	assert_eq!(other.vault_balance(&mut context).await, 0);
+}
+
+// Everything a frozen build rejects in `tests/frozen.rs`, against the same written-in tokenizer
+#[tokio::test]
+async fn normal_build_accepts_new_positions() {
+	let mut context = start().await;
+	let underlying_mint = create_mint(&mut context).await;
+	let user = create_user(&mut context, &underlying_mint, 1_000).await;
+	let tokenizer = Tokenizer::install(
+		&mut context,
+		underlying_mint,
+		START + 365 * DAY,
+		&user.pubkey(),
+		1_000,
+		100,
+	)
+	.await;
+	let payer = context.payer.pubkey();
+
+	let init = TokenizerConfig::new(payer, underlying_mint, Expiry::EighteenMonths)
+		.instructions(START)
+		.unwrap();
+	send(&mut context, &init, &[]).await.unwrap();
+
+	send(
+		&mut context,
+		&[
+			tokenizer.deposit_underlying(&user.pubkey(), 250),
+			tokenizer.tokenize_principal(&user.pubkey(), 250),
+			tokenizer.tokenize_yield(&user.pubkey(), 250),
+			tokenizer.deposit_and_tokenize(&user.pubkey(), 500),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 1_850);
+	assert_eq!(
+		tokenizer
+			.principal_balance(&mut context, &user.pubkey())
+			.await,
+		1_750
+	);
+}
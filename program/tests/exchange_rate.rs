@@ -0,0 +1,53 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{instruction, Expiry, RATE_SCALE},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::{signature::Signer, transaction::Transaction},
+};
+
+// Reads `GetExchangeRate`'s return data through a simulation
+async fn read_rate(context: &mut ProgramTestContext, tokenizer: &Tokenizer) -> u64 {
+	let transaction = Transaction::new_signed_with_payer(
+		&[instruction::get_exchange_rate(&tokenizer.address).unwrap()],
+		Some(&context.payer.pubkey()),
+		&[&context.payer],
+		context.last_blockhash,
+	);
+	let simulation = context
+		.banks_client
+		.simulate_transaction(transaction)
+		.await
+		.unwrap();
+	let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+
+	assert_eq!(return_data.program_id, sclr_token::id());
+	u64::from_le_bytes(return_data.data.try_into().unwrap())
+}
+
+#[tokio::test]
+async fn exchange_rate_converges_to_one_at_maturity() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 1_000).await;
+	let mut rates = Vec::new();
+
+	for now in [
+		START,
+		(START + tokenizer.expiry_date) / 2,
+		tokenizer.expiry_date - DAY,
+		tokenizer.expiry_date,
+		tokenizer.expiry_date + DAY,
+	] {
+		warp_to(&mut context, now).await;
+		rates.push(read_rate(&mut context, &tokenizer).await);
+	}
+
+	assert!(rates[0] < RATE_SCALE);
+	assert!(rates[..4].windows(2).all(|pair| pair[0] < pair[1]));
+	assert_eq!(rates[3], RATE_SCALE);
+	assert_eq!(rates[4], RATE_SCALE);
+}
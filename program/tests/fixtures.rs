@@ -0,0 +1,63 @@
+use {
+	sclr_token::{get_principal_mint_address, get_tokenizer_address, get_yield_mint_address},
+	solana_sdk::pubkey::Pubkey,
+	std::str::FromStr,
+};
+
+// 2026-01-01 UTC
+const EXPIRY_DATE: i64 = 1_767_225_600;
+
+// The addresses derived for a tokenizer of `underlying_mint` expiring at `EXPIRY_DATE`. Any
+// change to the seeds moves them, so these must only ever be updated deliberately.
+struct Fixture {
+	underlying_mint: &'static str,
+	tokenizer: &'static str,
+	principal_mint: &'static str,
+	yield_mint: &'static str,
+	vault: &'static str,
+}
+
+const FIXTURES: [Fixture; 2] = [
+	// USDC
+	Fixture {
+		underlying_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+		tokenizer: "c52WtUjnrAjBQvT5WuooYxfK7HEKBNHb1RMiy2qDhLG",
+		principal_mint: "3WVWDAbSW6JuCQLbpRxebNp3oyFS79puFudZFUcWJ65o",
+		yield_mint: "HD2uctXXfau9hqczhzrNeEmDCkz2bPNq1XHYjr3DMqZB",
+		vault: "BcyibTVRUcWq9UiRBeHnGxsXq9Ua2qEdDmsGoh2ueHvX",
+	},
+	// Wrapped SOL
+	Fixture {
+		underlying_mint: "So11111111111111111111111111111111111111112",
+		tokenizer: "5aHzMEp37K5cnhc4NbDWvwCdyTLUVtS3HjfcrmWLbqJR",
+		principal_mint: "BzPKaLcz3XuDCrCDxFqMdUz7ZJPMVnzRNUXDCSyGxY18",
+		yield_mint: "6t7R419Psjz76GH6egU54trxq8se7ZHfDkbrGYyb6yyC",
+		vault: "7z9GELuA4ZZ8yTsSjpnADxRJhPZMzWdx8SupakwG9vXe",
+	},
+];
+
+#[test]
+fn derived_addresses_match_fixtures() {
+	for fixture in FIXTURES {
+		let underlying_mint = Pubkey::from_str(fixture.underlying_mint).unwrap();
+		let (tokenizer, _) = get_tokenizer_address(&underlying_mint, EXPIRY_DATE);
+
+		assert_eq!(tokenizer.to_string(), fixture.tokenizer);
+		assert_eq!(
+			get_principal_mint_address(&tokenizer).0.to_string(),
+			fixture.principal_mint
+		);
+		assert_eq!(
+			get_yield_mint_address(&tokenizer).0.to_string(),
+			fixture.yield_mint
+		);
+		assert_eq!(
+			spl_associated_token_account::get_associated_token_address(
+				&tokenizer,
+				&underlying_mint
+			)
+			.to_string(),
+			fixture.vault
+		);
+	}
+}
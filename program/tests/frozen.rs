@@ -0,0 +1,87 @@
+// Only a frozen build rejects new positions
+#![cfg(feature = "frozen")]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{error::TokenizerError, instruction::TokenizerConfig, Expiry},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::signature::{Keypair, Signer},
+};
+
+// A tokenizer written into the bank with a user holding 1,000 of principal and yield, 100 of
+// yield and 1,000 of underlying
+async fn installed() -> (ProgramTestContext, Tokenizer, Keypair) {
+	let mut context = start().await;
+	let underlying_mint = create_mint(&mut context).await;
+	let user = create_user(&mut context, &underlying_mint, 1_000).await;
+	let tokenizer = Tokenizer::install(
+		&mut context,
+		underlying_mint,
+		START + 365 * DAY,
+		&user.pubkey(),
+		1_000,
+		100,
+	)
+	.await;
+
+	(context, tokenizer, user)
+}
+
+#[tokio::test]
+async fn frozen_build_rejects_new_positions() {
+	let (mut context, tokenizer, user) = installed().await;
+	let payer = context.payer.pubkey();
+
+	let init = TokenizerConfig::new(payer, tokenizer.underlying_mint, Expiry::EighteenMonths)
+		.instructions(START)
+		.unwrap();
+	let result = send(&mut context, &init, &[]).await;
+	assert_error(result, tokenizer_error(TokenizerError::ProgramFrozen));
+
+	for instruction in [
+		tokenizer.deposit_underlying(&user.pubkey(), 1_000),
+		tokenizer.tokenize_principal(&user.pubkey(), 1_000),
+		tokenizer.tokenize_yield(&user.pubkey(), 1_000),
+		tokenizer.deposit_and_tokenize(&user.pubkey(), 1_000),
+	] {
+		let result = send(&mut context, &[instruction], &[&user]).await;
+		assert_error(result, tokenizer_error(TokenizerError::ProgramFrozen));
+	}
+
+	assert_eq!(
+		tokenizer
+			.underlying_balance(&mut context, &user.pubkey())
+			.await,
+		1_000
+	);
+}
+
+#[tokio::test]
+async fn frozen_build_lets_positions_wind_down() {
+	let (mut context, tokenizer, user) = installed().await;
+	let address = user.pubkey();
+
+	send(
+		&mut context,
+		&[tokenizer.claim_yield(&address, 1_000, false)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+	send(
+		&mut context,
+		&[tokenizer.redeem_mature_principal(&address, 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		2_100
+	);
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 0);
+}
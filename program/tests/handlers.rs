@@ -0,0 +1,84 @@
+// A frozen build rejects deposits before any account is checked
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::{stub::*, *},
+	sclr_token::error::TokenizerError,
+	solana_sdk::{program_error::ProgramError, pubkey::Pubkey},
+};
+
+fn stub_tokenizer() -> Tokenizer {
+	set_clock(START);
+	Tokenizer::new(
+		Pubkey::new_unique(),
+		Pubkey::new_unique(),
+		START + 365 * DAY,
+	)
+}
+
+// A deposit of 100 into `tokenizer`, run against stub accounts with `vault`, owned by
+// `vault_owner` and holding `vault_data`, passed as its vault
+fn deposit(
+	tokenizer: &Tokenizer,
+	vault: Pubkey,
+	vault_owner: Pubkey,
+	vault_data: Vec<u8>,
+) -> Result<(), ProgramError> {
+	let mut instruction = tokenizer.deposit_underlying(&Pubkey::new_unique(), 100);
+	instruction.accounts[1].pubkey = vault;
+
+	let mut accounts = StubAccount::for_instruction(&instruction, |key| {
+		if key == &tokenizer.address {
+			Some((sclr_token::id(), tokenizer_state(tokenizer)))
+		} else if key == &vault {
+			Some((vault_owner, vault_data.clone()))
+		} else {
+			None
+		}
+	});
+
+	process(&instruction, &mut accounts)
+}
+
+#[test]
+fn deposit_rejects_another_token_account_as_vault() {
+	let tokenizer = stub_tokenizer();
+	let user = Pubkey::new_unique();
+
+	let result = deposit(
+		&tokenizer,
+		Pubkey::new_unique(),
+		spl_token::id(),
+		token_account(&tokenizer.underlying_mint, &user, 1_000),
+	);
+	assert_eq!(result, Err(TokenizerError::IncorrectVaultAddress.into()));
+}
+
+#[test]
+fn deposit_rejects_a_vault_not_owned_by_the_tokenizer() {
+	let tokenizer = stub_tokenizer();
+	let user = Pubkey::new_unique();
+
+	let result = deposit(
+		&tokenizer,
+		tokenizer.vault,
+		spl_token::id(),
+		token_account(&tokenizer.underlying_mint, &user, 1_000),
+	);
+	assert_eq!(result, Err(TokenizerError::IncorrectVaultAddress.into()));
+}
+
+#[test]
+fn deposit_rejects_a_vault_of_another_program() {
+	let tokenizer = stub_tokenizer();
+
+	let result = deposit(
+		&tokenizer,
+		tokenizer.vault,
+		Pubkey::new_unique(),
+		token_account(&tokenizer.underlying_mint, &tokenizer.address, 0),
+	);
+	assert_eq!(result, Err(TokenizerError::IncorrectVaultAddress.into()));
+}
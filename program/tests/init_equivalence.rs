@@ -0,0 +1,98 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{
+		instruction::{self, TokenizerConfig},
+		Expiry,
+	},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::{
+		account::AccountSharedData,
+		program_option::COption,
+		pubkey::Pubkey,
+		signature::{Keypair, Signer},
+		system_instruction,
+	},
+};
+
+// Funds the shared authority, which pays for the accounts it initializes
+async fn fund(context: &mut ProgramTestContext, authority: &Pubkey) {
+	let payer = context.payer.pubkey();
+	send(
+		context,
+		&[system_instruction::transfer(
+			&payer,
+			authority,
+			1_000_000_000,
+		)],
+		&[],
+	)
+	.await
+	.unwrap();
+}
+
+#[tokio::test]
+async fn combined_init_matches_separate_inits() {
+	// Both paths run in their own context with the same authority and underlying mint, so
+	// every derived address and every byte of the accounts they create should line up
+	let authority = Keypair::new();
+	let mut separate = start().await;
+	let mut combined = start().await;
+	fund(&mut separate, &authority.pubkey()).await;
+	fund(&mut combined, &authority.pubkey()).await;
+
+	for _ in 0..2 {
+		let underlying_mint = create_mint(&mut separate).await;
+		let mint_account = account(&mut separate, &underlying_mint).await.unwrap();
+		combined.set_account(&underlying_mint, &AccountSharedData::from(mint_account));
+
+		let tokenizer = Tokenizer::new(
+			authority.pubkey(),
+			underlying_mint,
+			Expiry::TwelveMonths.to_expiry_date(START).unwrap(),
+		);
+
+		// `TokenizerConfig` initializes the tokenizer and then its mints as two instructions
+		let init = TokenizerConfig::new(authority.pubkey(), underlying_mint, Expiry::TwelveMonths)
+			.instructions(START)
+			.unwrap();
+		send(&mut separate, &init, &[&authority]).await.unwrap();
+
+		let init = instruction::init_tokenizer_and_mints(
+			&tokenizer.address,
+			&authority.pubkey(),
+			&tokenizer.vault,
+			&underlying_mint,
+			&tokenizer.principal_mint,
+			&tokenizer.yield_mint,
+			Expiry::TwelveMonths,
+			tokenizer.expiry_date,
+			0,
+		)
+		.unwrap();
+		send(&mut combined, &[init], &[&authority]).await.unwrap();
+
+		for address in [
+			tokenizer.address,
+			tokenizer.vault,
+			tokenizer.principal_mint,
+			tokenizer.yield_mint,
+		] {
+			let expected = account(&mut separate, &address).await.unwrap();
+			let actual = account(&mut combined, &address).await.unwrap();
+			assert_eq!(actual.owner, expected.owner);
+			assert_eq!(actual.lamports, expected.lamports);
+			assert_eq!(actual.data, expected.data, "{}", address);
+		}
+
+		for mint_address in [tokenizer.principal_mint, tokenizer.yield_mint] {
+			let mint = mint(&mut combined, &mint_address).await;
+			assert_eq!(mint.mint_authority, COption::Some(tokenizer.address));
+			assert_eq!(mint.freeze_authority, COption::None);
+		}
+	}
+}
@@ -0,0 +1,144 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{error::TokenizerError, instruction, Expiry},
+	solana_sdk::signature::Signer,
+};
+
+// Initializes a 12 month tokenizer at `init_ts`, then its mints at `mints_ts`
+async fn init_across(
+	init_ts: i64,
+	mints_ts: i64,
+) -> Result<(), solana_program_test::BanksClientError> {
+	let mut context = start().await;
+	let underlying_mint = create_mint(&mut context).await;
+	let authority = context.payer.pubkey();
+
+	warp_to(&mut context, init_ts).await;
+	let expiry_date = Expiry::TwelveMonths.to_expiry_date(init_ts).unwrap();
+	let tokenizer = Tokenizer::new(authority, underlying_mint, expiry_date);
+
+	send(
+		&mut context,
+		&[instruction::init_tokenizer(
+			&tokenizer.address,
+			&authority,
+			&tokenizer.vault,
+			&underlying_mint,
+			&tokenizer.principal_mint,
+			&tokenizer.yield_mint,
+			Expiry::TwelveMonths,
+			expiry_date,
+			0,
+		)
+		.unwrap()],
+		&[],
+	)
+	.await
+	.unwrap();
+
+	warp_to(&mut context, mints_ts).await;
+	send(
+		&mut context,
+		&[instruction::init_mints(
+			&tokenizer.address,
+			&authority,
+			&underlying_mint,
+			&tokenizer.principal_mint,
+			&tokenizer.yield_mint,
+			Expiry::TwelveMonths,
+			expiry_date,
+		)
+		.unwrap()],
+		&[],
+	)
+	.await?;
+
+	assert!(tokenizer.state(&mut context).await.mints_initialized);
+	Ok(())
+}
+
+#[tokio::test]
+async fn init_mints_after_midnight() {
+	let midnight = START - START.rem_euclid(DAY) + DAY;
+
+	init_across(midnight - 30, midnight + 30).await.unwrap();
+}
+
+#[tokio::test]
+async fn init_mints_days_later() {
+	init_across(START, START + 3 * DAY).await.unwrap();
+}
+
+#[tokio::test]
+async fn init_tokenizer_with_date_resolved_before_midnight() {
+	let mut context = start().await;
+	let underlying_mint = create_mint(&mut context).await;
+	let midnight = START - START.rem_euclid(DAY) + DAY;
+
+	// The client resolved the date just before midnight, the cluster runs just after
+	let instructions = instruction::TokenizerConfig::new(
+		context.payer.pubkey(),
+		underlying_mint,
+		Expiry::TwelveMonths,
+	)
+	.instructions(midnight - 30)
+	.unwrap();
+	warp_to(&mut context, midnight + 30).await;
+
+	send(&mut context, &instructions, &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn init_mints_rejects_other_expiry_date() {
+	let mut context = start().await;
+	let underlying_mint = create_mint(&mut context).await;
+	let authority = context.payer.pubkey();
+	let expiry_date = Expiry::TwelveMonths.to_expiry_date(START).unwrap();
+	let tokenizer = Tokenizer::new(authority, underlying_mint, expiry_date);
+
+	send(
+		&mut context,
+		&[instruction::init_tokenizer(
+			&tokenizer.address,
+			&authority,
+			&tokenizer.vault,
+			&underlying_mint,
+			&tokenizer.principal_mint,
+			&tokenizer.yield_mint,
+			Expiry::TwelveMonths,
+			expiry_date,
+			0,
+		)
+		.unwrap()],
+		&[],
+	)
+	.await
+	.unwrap();
+
+	let result = send(
+		&mut context,
+		&[instruction::init_mints(
+			&tokenizer.address,
+			&authority,
+			&underlying_mint,
+			&tokenizer.principal_mint,
+			&tokenizer.yield_mint,
+			Expiry::TwelveMonths,
+			expiry_date + DAY,
+		)
+		.unwrap()],
+		&[],
+	)
+	.await;
+
+	// The tokenizer is derived from the expiry date, so another date names another tokenizer
+	assert_error(
+		result,
+		tokenizer_error(TokenizerError::IncorrectTokenizerAddress),
+	);
+}
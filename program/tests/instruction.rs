@@ -0,0 +1,288 @@
+mod common;
+
+use {
+	borsh::{to_vec, BorshDeserialize},
+	common::{builders, Tokenizer, DAY, START},
+	sclr_token::{
+		get_program_config_address,
+		instruction::{self, TokenizerConfig, TokenizerInstruction},
+		Expiry,
+	},
+	solana_sdk::{
+		instruction::Instruction, program_error::ProgramError, pubkey::Pubkey, system_program,
+	},
+};
+
+#[test]
+fn init_tokenizer_discriminant_and_accounts() {
+	let underlying_mint = Pubkey::new_unique();
+	let ix = instruction::init_tokenizer(
+		&Pubkey::new_unique(),
+		&Pubkey::new_unique(),
+		&Pubkey::new_unique(),
+		&underlying_mint,
+		&Pubkey::new_unique(),
+		&Pubkey::new_unique(),
+		Expiry::TwelveMonths,
+		1_735_689_600,
+		500,
+	)
+	.unwrap();
+
+	assert_eq!(ix.program_id, sclr_token::id());
+	assert_eq!(ix.data[0], 0);
+	assert_eq!(ix.accounts.len(), 8);
+	assert!(matches!(
+		TokenizerInstruction::try_from_slice(&ix.data).unwrap(),
+		TokenizerInstruction::InitializeTokenizer {
+			underlying_mint: mint,
+			expiry: Expiry::TwelveMonths,
+			expiry_date: 1_735_689_600,
+			fixed_apy: 500,
+			..
+		} if mint == underlying_mint
+	));
+}
+
+// Distinct addresses for each builder parameter, so a swapped pair shows up in the metas
+struct InitAddresses {
+	tokenizer: Pubkey,
+	authority: Pubkey,
+	underlying_vault: Pubkey,
+	underlying_mint: Pubkey,
+	principal_token_mint: Pubkey,
+	yield_token_mint: Pubkey,
+}
+
+impl InitAddresses {
+	fn new() -> Self {
+		Self {
+			tokenizer: Pubkey::new_unique(),
+			authority: Pubkey::new_unique(),
+			underlying_vault: Pubkey::new_unique(),
+			underlying_mint: Pubkey::new_unique(),
+			principal_token_mint: Pubkey::new_unique(),
+			yield_token_mint: Pubkey::new_unique(),
+		}
+	}
+}
+
+fn meta_keys(ix: &Instruction) -> Vec<Pubkey> {
+	ix.accounts.iter().map(|meta| meta.pubkey).collect()
+}
+
+#[test]
+fn init_tokenizer_maps_parameters_to_metas() {
+	let a = InitAddresses::new();
+	let ix = instruction::init_tokenizer(
+		&a.tokenizer,
+		&a.authority,
+		&a.underlying_vault,
+		&a.underlying_mint,
+		&a.principal_token_mint,
+		&a.yield_token_mint,
+		Expiry::TwelveMonths,
+		0,
+		0,
+	)
+	.unwrap();
+
+	assert_eq!(
+		meta_keys(&ix),
+		[
+			a.tokenizer,
+			a.authority,
+			a.underlying_vault,
+			a.underlying_mint,
+			spl_token::id(),
+			system_program::id(),
+			spl_associated_token_account::id(),
+			get_program_config_address().0,
+		]
+	);
+}
+
+#[test]
+fn init_mints_maps_parameters_to_metas() {
+	let a = InitAddresses::new();
+	let ix = instruction::init_mints(
+		&a.tokenizer,
+		&a.authority,
+		&a.underlying_mint,
+		&a.principal_token_mint,
+		&a.yield_token_mint,
+		Expiry::TwelveMonths,
+		0,
+	)
+	.unwrap();
+
+	assert_eq!(
+		meta_keys(&ix),
+		[
+			a.tokenizer,
+			a.authority,
+			a.underlying_mint,
+			a.principal_token_mint,
+			a.yield_token_mint,
+			spl_token::id(),
+			system_program::id(),
+		]
+	);
+}
+
+#[test]
+fn init_tokenizer_and_mints_maps_parameters_to_metas() {
+	let a = InitAddresses::new();
+	let ix = instruction::init_tokenizer_and_mints(
+		&a.tokenizer,
+		&a.authority,
+		&a.underlying_vault,
+		&a.underlying_mint,
+		&a.principal_token_mint,
+		&a.yield_token_mint,
+		Expiry::TwelveMonths,
+		0,
+		0,
+	)
+	.unwrap();
+
+	assert_eq!(
+		meta_keys(&ix),
+		[
+			a.tokenizer,
+			a.authority,
+			a.underlying_vault,
+			a.underlying_mint,
+			a.principal_token_mint,
+			a.yield_token_mint,
+			spl_token::id(),
+			system_program::id(),
+			spl_associated_token_account::id(),
+			get_program_config_address().0,
+		]
+	);
+}
+
+fn hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Golden bytes: a borsh upgrade that changes the enum tag width or integer encoding breaks
+// every client already deployed, so it must fail here first
+#[test]
+fn deposit_underlying_wire_format() {
+	let ix = TokenizerInstruction::DepositUnderlying { amount: 42 };
+
+	assert_eq!(hex(&to_vec(&ix).unwrap()), "032a00000000000000");
+	assert!(matches!(
+		TokenizerInstruction::try_from_slice(&[3, 42, 0, 0, 0, 0, 0, 0, 0]).unwrap(),
+		TokenizerInstruction::DepositUnderlying { amount: 42 }
+	));
+}
+
+#[test]
+fn expiry_wire_format() {
+	assert_eq!(hex(&to_vec(&Expiry::EighteenMonths).unwrap()), "01");
+	assert_eq!(
+		Expiry::try_from_slice(&[1]).unwrap(),
+		Expiry::EighteenMonths
+	);
+}
+
+// `(is_signer, is_writable)` of an account meta
+const R: (bool, bool) = (false, false);
+const W: (bool, bool) = (false, true);
+const S: (bool, bool) = (true, false);
+const SW: (bool, bool) = (true, true);
+
+// The signer and writable flags of every meta of every builder, in order. Marking an account
+// read-only that a handler writes to fails the transaction, and marking one signer or writable
+// that need not be asks more of the client than the handler does.
+#[test]
+fn builder_meta_flags() {
+	let expected: [(&str, &[(bool, bool)]); 28] = [
+		("init_tokenizer", &[W, SW, W, R, R, R, R, R]),
+		("init_mints", &[W, SW, R, W, W, R, R]),
+		("init_tokenizer_and_mints", &[W, SW, W, R, W, W, R, R, R, R]),
+		("deposit_underlying", &[W, W, SW, W, R, R, W, R, R]),
+		("tokenize_principal", &[W, W, SW, W, R, R, R, W, R, R]),
+		("tokenize_yield", &[W, W, SW, W, R, R, R, W, R, R]),
+		(
+			"deposit_and_tokenize",
+			&[W, W, W, W, SW, W, W, W, R, R, R, W],
+		),
+		(
+			"redeem_mature_principal",
+			&[W, W, R, W, SW, W, W, R, R, R, R],
+		),
+		(
+			"redeem_principal_and_yield",
+			&[W, W, R, W, W, SW, W, W, W, R, R, R, W],
+		),
+		("unwind", &[W, W, R, W, W, SW, W, W, W, R, R, R, W]),
+		("claim_yield", &[W, W, R, R, W, SW, W, W, R, R, R, W]),
+		("batch_redeem", &[W, W, R, W, W, R, R, R, SW, W, W, W, W]),
+		("update_rebasing", &[W, S, R, R]),
+		("terminate", &[W, SW, W, W, W, R, R, R, W, R]),
+		("terminate_tokenizer", &[W, SW, W, R, R, R, W, R]),
+		("terminate_mints", &[W, SW, W, W, R, R]),
+		("terminate_principal_mint", &[W, SW, W, R]),
+		("get_exchange_rate", &[R]),
+		("reconcile", &[R, R, R, R]),
+		("recreate_vault", &[R, SW, W, R, R, R, R]),
+		("update_fixed_apy", &[W, S, R, R]),
+		("update_min_hold", &[W, S]),
+		("init_config", &[W, SW, R, R]),
+		("update_config", &[W, S]),
+		("initialize_amm", &[W, R, SW, W, W, W, R, R, R, R, R]),
+		("add_liquidity", &[W, W, W, W, SW, W, W, W, R, R, R]),
+		("remove_liquidity", &[W, W, W, W, S, W, W, W, R]),
+		("swap", &[W, W, W, S, W, W, R]),
+	];
+	let tokenizer = Tokenizer::new(
+		Pubkey::new_unique(),
+		Pubkey::new_unique(),
+		START + 365 * DAY,
+	);
+	let built = builders(&tokenizer, Pubkey::new_unique());
+	assert_eq!(built.len(), expected.len());
+
+	for ((name, ix), (expected_name, expected_flags)) in built.iter().zip(expected) {
+		assert_eq!(*name, expected_name);
+		let flags: Vec<(bool, bool)> = ix
+			.accounts
+			.iter()
+			.map(|meta| (meta.is_signer, meta.is_writable))
+			.collect();
+		assert_eq!(flags, expected_flags, "{name}");
+	}
+}
+
+#[test]
+fn tokenizer_config_validate() {
+	let config = TokenizerConfig::new(
+		Pubkey::new_unique(),
+		Pubkey::new_unique(),
+		Expiry::TwelveMonths,
+	);
+	assert_eq!(config.clone().fixed_apy(10_000).validate(), Ok(()));
+	assert_eq!(
+		config.clone().fixed_apy(10_001).validate(),
+		Err(ProgramError::InvalidArgument)
+	);
+
+	let default_mint = TokenizerConfig {
+		underlying_mint: Pubkey::default(),
+		..config.clone()
+	};
+	assert_eq!(default_mint.validate(), Err(ProgramError::InvalidArgument));
+
+	let default_authority = TokenizerConfig {
+		authority: Pubkey::default(),
+		..config
+	};
+	assert_eq!(
+		default_authority.validate(),
+		Err(ProgramError::InvalidArgument)
+	);
+}
@@ -0,0 +1,53 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {common::*, sclr_token::Expiry, solana_sdk::signature::Signer};
+
+#[tokio::test]
+async fn deposit_redeems_for_deposit_plus_yield() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 500).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+	let address = user.pubkey();
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&address, 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	// Principal is minted 1:1 whatever the fixed APY, which only prices it before maturity
+	assert_eq!(
+		tokenizer.principal_balance(&mut context, &address).await,
+		1_000
+	);
+	assert_eq!(tokenizer.yield_balance(&mut context, &address).await, 1_000);
+
+	tokenizer.accrue(&mut context, 50).await;
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+
+	send(
+		&mut context,
+		&[
+			tokenizer.redeem_mature_principal(&address, 1_000),
+			tokenizer.claim_yield(&address, 1_000, false),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		1_050
+	);
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 0);
+	assert_eq!(
+		mint(&mut context, &tokenizer.principal_mint).await.supply,
+		0
+	);
+}
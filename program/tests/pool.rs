@@ -0,0 +1,96 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	borsh::BorshDeserialize,
+	common::*,
+	sclr_token::{get_pool_address, instruction, pool::PoolState, Expiry},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::{
+		instruction::InstructionError,
+		pubkey::Pubkey,
+		signature::{Keypair, Signer},
+	},
+};
+
+// A tokenizer with a pool holding one user's 1,000 principal against 1,000 underlying, the user
+// keeping 100 underlying
+async fn pooled() -> (ProgramTestContext, Tokenizer, Keypair) {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 2_100).await;
+	let address = user.pubkey();
+	let authority = context.payer.pubkey();
+
+	send(
+		&mut context,
+		&[
+			tokenizer.deposit_and_tokenize(&address, 1_000),
+			instruction::initialize_amm(
+				&tokenizer.address,
+				&authority,
+				&tokenizer.principal_mint,
+				&tokenizer.underlying_mint,
+				30,
+			)
+			.unwrap(),
+			instruction::add_liquidity(
+				&tokenizer.address,
+				&tokenizer.principal_mint,
+				&tokenizer.underlying_mint,
+				&address,
+				1_000,
+				1_000,
+				1_000,
+			)
+			.unwrap(),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	(context, tokenizer, user)
+}
+
+#[tokio::test]
+async fn pool_records_the_token_program() {
+	let (mut context, tokenizer, _) = pooled().await;
+	let (pool, _) = get_pool_address(&tokenizer.address);
+
+	let state =
+		PoolState::try_from_slice(&account(&mut context, &pool).await.unwrap().data).unwrap();
+	assert_eq!(state.token_program, spl_token::id());
+	assert_eq!(state.principal_reserve, 1_000);
+	assert_eq!(state.underlying_reserve, 1_000);
+}
+
+#[tokio::test]
+async fn swap_rejects_another_token_program() {
+	let (mut context, tokenizer, user) = pooled().await;
+	let address = user.pubkey();
+	let swap = |token_program: Pubkey| {
+		let mut swap = instruction::swap(
+			&tokenizer.address,
+			&tokenizer.principal_mint,
+			&tokenizer.underlying_mint,
+			&address,
+			false,
+			100,
+			0,
+		)
+		.unwrap();
+		swap.accounts[6].pubkey = token_program;
+		swap
+	};
+
+	let result = send(&mut context, &[swap(Pubkey::new_unique())], &[&user]).await;
+	assert_error(result, InstructionError::IncorrectProgramId);
+
+	send(&mut context, &[swap(spl_token::id())], &[&user])
+		.await
+		.unwrap();
+	assert!(tokenizer.principal_balance(&mut context, &address).await > 0);
+}
@@ -0,0 +1,85 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {common::*, sclr_token::Expiry, solana_sdk::signature::Signer};
+
+#[tokio::test]
+async fn users_with_different_deposit_times_claim_independently() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let early = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+	let late = create_user(&mut context, &tokenizer.underlying_mint, 500).await;
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&early.pubkey(), 1_000)],
+		&[&early],
+	)
+	.await
+	.unwrap();
+	warp_to(&mut context, START + 30 * DAY).await;
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&late.pubkey(), 500)],
+		&[&late],
+	)
+	.await
+	.unwrap();
+	tokenizer.accrue(&mut context, 150).await;
+
+	send(
+		&mut context,
+		&[tokenizer.claim_yield(&early.pubkey(), 100, true)],
+		&[&early],
+	)
+	.await
+	.unwrap();
+
+	let position = tokenizer.position(&mut context, &early.pubkey()).await;
+	assert_eq!(position.principal_minted, 1_000);
+	assert_eq!(position.deposit_timestamp, START);
+	assert_eq!(position.yield_claimed, 100);
+
+	// The other user's claim is untouched by the first
+	let position = tokenizer.position(&mut context, &late.pubkey()).await;
+	assert_eq!(position.principal_minted, 500);
+	assert_eq!(position.deposit_timestamp, START + 30 * DAY);
+	assert_eq!(position.yield_claimed, 0);
+
+	send(
+		&mut context,
+		&[tokenizer.claim_yield(&late.pubkey(), 50, true)],
+		&[&late],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer
+			.position(&mut context, &late.pubkey())
+			.await
+			.yield_claimed,
+		50
+	);
+	assert_eq!(
+		tokenizer
+			.position(&mut context, &early.pubkey())
+			.await
+			.yield_claimed,
+		100
+	);
+	assert_eq!(
+		tokenizer
+			.underlying_balance(&mut context, &early.pubkey())
+			.await,
+		100
+	);
+	assert_eq!(
+		tokenizer
+			.underlying_balance(&mut context, &late.pubkey())
+			.await,
+		50
+	);
+}
@@ -0,0 +1,113 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{instruction::RedeemOrder, Expiry},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::signature::{Keypair, Signer},
+};
+
+// A rebasing 12 month tokenizer holding one user's tokenized 1,000 deposit, after the vault
+// rebased to `vault_balance`
+async fn rebased(vault_balance: u64) -> (ProgramTestContext, Tokenizer, Keypair) {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+
+	send(&mut context, &[tokenizer.update_rebasing(true)], &[])
+		.await
+		.unwrap();
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&user.pubkey(), 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	tokenizer
+		.set_vault_balance(&mut context, vault_balance)
+		.await;
+
+	(context, tokenizer, user)
+}
+
+#[tokio::test]
+async fn rebase_up_is_claimable_as_yield() {
+	let (mut context, tokenizer, user) = rebased(1_100).await;
+	let address = user.pubkey();
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+
+	// Principal is still redeemed 1:1, the gain stays in the vault for the yield holders
+	send(
+		&mut context,
+		&[tokenizer.redeem_mature_principal(&address, 500)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		500
+	);
+
+	send(
+		&mut context,
+		&[tokenizer.claim_yield(&address, 1_000, false)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		600
+	);
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 500);
+}
+
+#[tokio::test]
+async fn rebase_up_pair_redemption_is_order_independent() {
+	for redeem_order in [RedeemOrder::PrincipalFirst, RedeemOrder::YieldFirst] {
+		let (mut context, tokenizer, user) = rebased(1_100).await;
+		let address = user.pubkey();
+
+		send(
+			&mut context,
+			&[tokenizer.redeem_principal_and_yield(&address, 500, 600, redeem_order)],
+			&[&user],
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(
+			tokenizer.underlying_balance(&mut context, &address).await,
+			600
+		);
+		assert_eq!(tokenizer.vault_balance(&mut context).await, 500);
+	}
+}
+
+#[tokio::test]
+async fn rebase_down_is_shared_by_principal() {
+	for redeem_order in [RedeemOrder::PrincipalFirst, RedeemOrder::YieldFirst] {
+		let (mut context, tokenizer, user) = rebased(900).await;
+		let address = user.pubkey();
+
+		send(
+			&mut context,
+			&[tokenizer.redeem_principal_and_yield(&address, 500, 450, redeem_order)],
+			&[&user],
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(
+			tokenizer.underlying_balance(&mut context, &address).await,
+			450
+		);
+		assert_eq!(tokenizer.vault_balance(&mut context).await, 450);
+		assert_eq!(mint(&mut context, &tokenizer.yield_mint).await.supply, 500);
+	}
+}
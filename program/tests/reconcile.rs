@@ -0,0 +1,111 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{instruction, Expiry},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::{
+		signature::{Keypair, Signer},
+		transaction::Transaction,
+	},
+};
+
+// A 12 month tokenizer at a 10% fixed APY holding one user's tokenized 1,000 deposit
+async fn tokenized() -> (ProgramTestContext, Tokenizer, Keypair) {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 1_000).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&user.pubkey(), 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	(context, tokenizer, user)
+}
+
+// Reads `Reconcile`'s return data through a simulation, as the healthy flag followed by the
+// vault balance, principal supply and yield supply
+async fn reconcile(
+	context: &mut ProgramTestContext,
+	tokenizer: &Tokenizer,
+) -> (bool, u64, u64, u64) {
+	let blockhash = context.get_new_latest_blockhash().await.unwrap();
+	let transaction = Transaction::new_signed_with_payer(
+		&[instruction::reconcile(
+			&tokenizer.address,
+			&tokenizer.vault,
+			&tokenizer.principal_mint,
+			&tokenizer.yield_mint,
+		)
+		.unwrap()],
+		Some(&context.payer.pubkey()),
+		&[&context.payer],
+		blockhash,
+	);
+	let simulation = context
+		.banks_client
+		.simulate_transaction(transaction)
+		.await
+		.unwrap();
+	let data = simulation
+		.simulation_details
+		.unwrap()
+		.return_data
+		.unwrap()
+		.data;
+	let number = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+
+	(data[0] == 1, number(1), number(9), number(17))
+}
+
+#[tokio::test]
+async fn reconcile_a_healthy_tokenizer() {
+	let (mut context, tokenizer, _) = tokenized().await;
+
+	assert_eq!(
+		reconcile(&mut context, &tokenizer).await,
+		(true, 1_000, 1_000, 1_000)
+	);
+}
+
+#[tokio::test]
+async fn reconcile_reports_diverging_supplies() {
+	let (mut context, tokenizer, user) = tokenized().await;
+
+	send(
+		&mut context,
+		&[tokenizer.burn_yield(&user.pubkey(), 100)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		reconcile(&mut context, &tokenizer).await,
+		(false, 1_000, 1_000, 900)
+	);
+
+	// Past maturity the principal is redeemed on its own and the supplies may differ
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+	assert!(reconcile(&mut context, &tokenizer).await.0);
+}
+
+#[tokio::test]
+async fn reconcile_requires_principal_backed_one_to_one() {
+	let (mut context, tokenizer, _) = tokenized().await;
+
+	// Enough for the principal's discounted value a year out, but not for its face value
+	tokenizer.set_vault_balance(&mut context, 950).await;
+
+	assert_eq!(
+		reconcile(&mut context, &tokenizer).await,
+		(false, 950, 1_000, 1_000)
+	);
+}
@@ -0,0 +1,166 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{error::TokenizerError, Expiry},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::signature::{Keypair, Signer},
+};
+
+// A 12 month tokenizer holding one user's tokenized 1,000 deposit, with the vault then
+// overwritten to hold `vault_balance`
+async fn imbalanced(vault_balance: u64) -> (ProgramTestContext, Tokenizer, Keypair) {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_100).await;
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&user.pubkey(), 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	tokenizer
+		.set_vault_balance(&mut context, vault_balance)
+		.await;
+
+	(context, tokenizer, user)
+}
+
+#[tokio::test]
+async fn deposit_rejected_when_vault_is_short() {
+	let (mut context, tokenizer, user) = imbalanced(900).await;
+
+	// 1,000 in the vault against 1,100 of principal
+	let result = send(
+		&mut context,
+		&[tokenizer.deposit_underlying(&user.pubkey(), 100)],
+		&[&user],
+	)
+	.await;
+	assert_error(result, tokenizer_error(TokenizerError::VaultInsolvent));
+}
+
+#[tokio::test]
+async fn tokenize_rejected_when_vault_is_short() {
+	let (mut context, tokenizer, user) = imbalanced(1_000).await;
+	let address = user.pubkey();
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_underlying(&address, 100)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	tokenizer.set_vault_balance(&mut context, 1_000).await;
+
+	// 1,000 in the vault against 1,100 of principal, tokenized or not
+	for instruction in [
+		tokenizer.tokenize_principal(&address, 100),
+		tokenizer.tokenize_yield(&address, 100),
+	] {
+		let result = send(&mut context, &[instruction], &[&user]).await;
+		assert_error(result, tokenizer_error(TokenizerError::VaultInsolvent));
+	}
+}
+
+#[tokio::test]
+async fn redeem_rejected_when_vault_is_short() {
+	let (mut context, tokenizer, user) = imbalanced(900).await;
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+
+	let result = send(
+		&mut context,
+		&[tokenizer.redeem_mature_principal(&user.pubkey(), 100)],
+		&[&user],
+	)
+	.await;
+	assert_error(result, tokenizer_error(TokenizerError::VaultInsolvent));
+}
+
+#[tokio::test]
+async fn claim_rejected_when_vault_is_short() {
+	let (mut context, tokenizer, user) = imbalanced(900).await;
+
+	// Nothing is claimable, but the shortfall still fails the instruction
+	let result = send(
+		&mut context,
+		&[tokenizer.claim_yield(&user.pubkey(), 100, false)],
+		&[&user],
+	)
+	.await;
+	assert_error(result, tokenizer_error(TokenizerError::VaultInsolvent));
+}
+
+#[tokio::test]
+async fn instructions_allowed_with_surplus() {
+	let (mut context, tokenizer, user) = imbalanced(1_100).await;
+	let address = user.pubkey();
+
+	send(
+		&mut context,
+		&[
+			tokenizer.deposit_underlying(&address, 100),
+			tokenizer.claim_yield(&address, 100, true),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+	send(
+		&mut context,
+		&[tokenizer.redeem_mature_principal(&address, 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 100);
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		1_100
+	);
+}
+
+#[tokio::test]
+async fn rebasing_vault_may_fall_below_principal() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+	let address = user.pubkey();
+
+	send(&mut context, &[tokenizer.update_rebasing(true)], &[])
+		.await
+		.unwrap();
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&address, 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	tokenizer.set_vault_balance(&mut context, 900).await;
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+
+	// Principal is redeemed pro rata from what the vault holds
+	send(
+		&mut context,
+		&[tokenizer.redeem_mature_principal(&address, 500)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		450
+	);
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 450);
+}
@@ -0,0 +1,380 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{error::TokenizerError, instruction, state::STATE_SIZE, Expiry},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::{
+		account::{Account, AccountSharedData},
+		message::Message,
+		program_option::COption,
+		signature::{Keypair, Signer},
+	},
+};
+
+// A 12 month tokenizer holding one user's 1,000 deposit and 100 of yield, past its expiry
+async fn matured() -> (ProgramTestContext, Tokenizer, Keypair) {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&user.pubkey(), 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	tokenizer.accrue(&mut context, 100).await;
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+
+	(context, tokenizer, user)
+}
+
+#[tokio::test]
+async fn claim_yield_after_terminating_principal_mint() {
+	let (mut context, tokenizer, user) = matured().await;
+	let address = user.pubkey();
+	let authority = context.payer.pubkey();
+	let terminate_principal_mint = instruction::terminate_principal_mint(
+		&tokenizer.address,
+		&authority,
+		&tokenizer.principal_mint,
+	)
+	.unwrap();
+
+	let result = send(
+		&mut context,
+		std::slice::from_ref(&terminate_principal_mint),
+		&[],
+	)
+	.await;
+	assert_error(result, tokenizer_error(TokenizerError::MintSupplyNotZero));
+
+	send(
+		&mut context,
+		&[tokenizer.redeem_mature_principal(&address, 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	send(&mut context, &[terminate_principal_mint], &[])
+		.await
+		.unwrap();
+
+	// The mint is left in place with nobody able to mint more
+	let principal_mint = mint(&mut context, &tokenizer.principal_mint).await;
+	assert_eq!(principal_mint.mint_authority, COption::None);
+	assert_eq!(principal_mint.supply, 0);
+	assert!(
+		tokenizer
+			.state(&mut context)
+			.await
+			.principal_mint_terminated
+	);
+
+	send(
+		&mut context,
+		&[tokenizer.claim_yield(&address, 1_000, false)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		1_100
+	);
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 0);
+}
+
+#[tokio::test]
+async fn terminate_revokes_mints_and_closes_tokenizer() {
+	let (mut context, tokenizer, user) = matured().await;
+	let address = user.pubkey();
+	let authority = context.payer.pubkey();
+
+	send(
+		&mut context,
+		&[
+			tokenizer.redeem_mature_principal(&address, 1_000),
+			tokenizer.claim_yield(&address, 1_000, false),
+			tokenizer.burn_yield(&address, 900),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	send(
+		&mut context,
+		&[instruction::terminate(
+			&tokenizer.address,
+			&authority,
+			&tokenizer.vault,
+			&tokenizer.underlying_mint,
+			&tokenizer.principal_mint,
+			&tokenizer.yield_mint,
+		)
+		.unwrap()],
+		&[],
+	)
+	.await
+	.unwrap();
+
+	for mint_address in [tokenizer.principal_mint, tokenizer.yield_mint] {
+		assert_eq!(
+			mint(&mut context, &mint_address).await.mint_authority,
+			COption::None
+		);
+	}
+	assert!(account(&mut context, &tokenizer.address).await.is_none());
+	assert!(account(&mut context, &tokenizer.vault).await.is_none());
+}
+
+#[tokio::test]
+async fn terminate_mints_then_tokenizer() {
+	let (mut context, tokenizer, user) = matured().await;
+	let address = user.pubkey();
+	let authority = context.payer.pubkey();
+
+	send(
+		&mut context,
+		&[
+			tokenizer.redeem_mature_principal(&address, 1_000),
+			tokenizer.claim_yield(&address, 1_000, false),
+			tokenizer.burn_yield(&address, 900),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	let terminate_mints = instruction::terminate_mints(
+		&tokenizer.address,
+		&authority,
+		&tokenizer.principal_mint,
+		&tokenizer.yield_mint,
+	)
+	.unwrap();
+	send(&mut context, std::slice::from_ref(&terminate_mints), &[])
+		.await
+		.unwrap();
+
+	let state = tokenizer.state(&mut context).await;
+	assert!(state.mints_terminated);
+	assert!(state.principal_mint_terminated);
+
+	// Terminating again is a no-op rather than a failure
+	send(&mut context, &[terminate_mints], &[]).await.unwrap();
+
+	send(
+		&mut context,
+		&[instruction::terminate_tokenizer(
+			&tokenizer.address,
+			&authority,
+			&tokenizer.vault,
+			&tokenizer.underlying_mint,
+		)
+		.unwrap()],
+		&[],
+	)
+	.await
+	.unwrap();
+
+	assert!(account(&mut context, &tokenizer.address).await.is_none());
+}
+
+#[tokio::test]
+async fn terminate_rejects_outstanding_tokens() {
+	let (mut context, tokenizer, user) = matured().await;
+	let address = user.pubkey();
+	let authority = context.payer.pubkey();
+	let terminate = instruction::terminate(
+		&tokenizer.address,
+		&authority,
+		&tokenizer.vault,
+		&tokenizer.underlying_mint,
+		&tokenizer.principal_mint,
+		&tokenizer.yield_mint,
+	)
+	.unwrap();
+	let terminate_mints = instruction::terminate_mints(
+		&tokenizer.address,
+		&authority,
+		&tokenizer.principal_mint,
+		&tokenizer.yield_mint,
+	)
+	.unwrap();
+
+	let result = send(&mut context, std::slice::from_ref(&terminate), &[]).await;
+	assert_error(result, tokenizer_error(TokenizerError::MintSupplyNotZero));
+
+	// Principal alone is not enough, the yield is still owed its share of the vault
+	send(
+		&mut context,
+		&[tokenizer.redeem_mature_principal(&address, 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	let result = send(&mut context, std::slice::from_ref(&terminate_mints), &[]).await;
+	assert_error(result, tokenizer_error(TokenizerError::MintSupplyNotZero));
+	let result = send(&mut context, std::slice::from_ref(&terminate), &[]).await;
+	assert_error(result, tokenizer_error(TokenizerError::MintSupplyNotZero));
+
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 100);
+	assert!(!tokenizer.state(&mut context).await.mints_terminated);
+
+	// Only 100 of the yield tokens were backed, the rest are burned to allow termination
+	send(
+		&mut context,
+		&[
+			tokenizer.claim_yield(&address, 1_000, false),
+			tokenizer.burn_yield(&address, 900),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	send(&mut context, &[terminate], &[]).await.unwrap();
+
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		1_100
+	);
+}
+
+#[tokio::test]
+async fn reallocated_tokenizer_is_rejected() {
+	let (mut context, tokenizer, user) = matured().await;
+	let address = user.pubkey();
+	let authority = context.payer.pubkey();
+
+	send(
+		&mut context,
+		&[
+			tokenizer.redeem_mature_principal(&address, 1_000),
+			tokenizer.claim_yield(&address, 1_000, false),
+			tokenizer.burn_yield(&address, 900),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	let mut state = tokenizer.state(&mut context).await;
+	send(
+		&mut context,
+		&[instruction::terminate(
+			&tokenizer.address,
+			&authority,
+			&tokenizer.vault,
+			&tokenizer.underlying_mint,
+			&tokenizer.principal_mint,
+			&tokenizer.yield_mint,
+		)
+		.unwrap()],
+		&[],
+	)
+	.await
+	.unwrap();
+
+	// Bring the account back at the same address holding what terminate left behind: the old
+	// state with only the discriminator cleared, and then nothing but zeroes
+	state.is_initialized = false;
+	for data in [borsh::to_vec(&state).unwrap(), vec![0; STATE_SIZE]] {
+		context.set_account(
+			&tokenizer.address,
+			&AccountSharedData::from(Account {
+				lamports: 1_000_000_000,
+				data,
+				owner: sclr_token::id(),
+				executable: false,
+				rent_epoch: 0,
+			}),
+		);
+
+		for instruction in [
+			tokenizer.deposit_underlying(&address, 100),
+			tokenizer.redeem_mature_principal(&address, 100),
+			tokenizer.claim_yield(&address, 100, false),
+		] {
+			let result = send(&mut context, &[instruction], &[&user]).await;
+			assert_error(
+				result,
+				tokenizer_error(TokenizerError::TokenizerNotInitialized),
+			);
+		}
+	}
+}
+
+#[tokio::test]
+async fn terminate_returns_rent_to_authority_once() {
+	let (mut context, tokenizer, user) = matured().await;
+	let address = user.pubkey();
+	let authority = context.payer.pubkey();
+
+	send(
+		&mut context,
+		&[
+			tokenizer.redeem_mature_principal(&address, 1_000),
+			tokenizer.claim_yield(&address, 1_000, false),
+			tokenizer.burn_yield(&address, 900),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	let terminate = instruction::terminate(
+		&tokenizer.address,
+		&authority,
+		&tokenizer.vault,
+		&tokenizer.underlying_mint,
+		&tokenizer.principal_mint,
+		&tokenizer.yield_mint,
+	)
+	.unwrap();
+	// The authority also pays for the transaction
+	let blockhash = context.get_new_latest_blockhash().await.unwrap();
+	let fee = context
+		.banks_client
+		.get_fee_for_message(Message::new_with_blockhash(
+			std::slice::from_ref(&terminate),
+			Some(&authority),
+			&blockhash,
+		))
+		.await
+		.unwrap()
+		.unwrap();
+	let authority_lamports = lamports(&mut context, &authority).await;
+	let vault_rent = lamports(&mut context, &tokenizer.vault).await;
+	let tokenizer_rent = lamports(&mut context, &tokenizer.address).await;
+	let principal_mint_rent = lamports(&mut context, &tokenizer.principal_mint).await;
+	let yield_mint_rent = lamports(&mut context, &tokenizer.yield_mint).await;
+
+	send(&mut context, &[terminate], &[]).await.unwrap();
+
+	assert_eq!(
+		lamports(&mut context, &authority).await,
+		authority_lamports + vault_rent + tokenizer_rent - fee
+	);
+	assert_eq!(lamports(&mut context, &tokenizer.address).await, 0);
+	assert!(account(&mut context, &tokenizer.address).await.is_none());
+	assert!(account(&mut context, &tokenizer.vault).await.is_none());
+
+	// The mints are not closed, their authority is revoked instead, so they keep their rent
+	assert_eq!(
+		lamports(&mut context, &tokenizer.principal_mint).await,
+		principal_mint_rent
+	);
+	assert_eq!(
+		lamports(&mut context, &tokenizer.yield_mint).await,
+		yield_mint_rent
+	);
+}
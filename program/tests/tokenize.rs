@@ -0,0 +1,166 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{error::TokenizerError, Expiry},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::{
+		instruction::InstructionError,
+		signature::{Keypair, Signer},
+	},
+};
+
+async fn deposited(amount: u64) -> (ProgramTestContext, Tokenizer, Keypair) {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, amount).await;
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_underlying(&user.pubkey(), amount)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	(context, tokenizer, user)
+}
+
+#[tokio::test]
+async fn tokenize_up_to_the_deposit() {
+	let (mut context, tokenizer, user) = deposited(1_000).await;
+	let address = user.pubkey();
+
+	let position = tokenizer.position(&mut context, &address).await;
+	assert_eq!(position.untokenized_principal, 1_000);
+	assert_eq!(position.untokenized_yield, 1_000);
+
+	send(
+		&mut context,
+		&[
+			tokenizer.tokenize_principal(&address, 600),
+			tokenizer.tokenize_principal(&address, 400),
+			tokenizer.tokenize_yield(&address, 1_000),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer.principal_balance(&mut context, &address).await,
+		1_000
+	);
+	assert_eq!(tokenizer.yield_balance(&mut context, &address).await, 1_000);
+
+	let position = tokenizer.position(&mut context, &address).await;
+	assert_eq!(position.untokenized_principal, 0);
+	assert_eq!(position.untokenized_yield, 0);
+	let state = tokenizer.state(&mut context).await;
+	assert_eq!(state.untokenized_principal, 0);
+	assert_eq!(state.untokenized_yield, 0);
+
+	for instruction in [
+		tokenizer.tokenize_principal(&address, 1),
+		tokenizer.tokenize_yield(&address, 1),
+	] {
+		let result = send(&mut context, &[instruction], &[&user]).await;
+		assert_error(result, tokenizer_error(TokenizerError::InsufficientFunds));
+	}
+}
+
+#[tokio::test]
+async fn tokenize_rejects_another_users_deposit() {
+	let (mut context, tokenizer, _) = deposited(1_000).await;
+	let other = create_user(&mut context, &tokenizer.underlying_mint, 0).await;
+	let address = other.pubkey();
+
+	for instruction in [
+		tokenizer.tokenize_principal(&address, 1),
+		tokenizer.tokenize_yield(&address, 1),
+	] {
+		let result = send(&mut context, &[instruction], &[&other]).await;
+		assert_error(result, tokenizer_error(TokenizerError::InsufficientFunds));
+	}
+
+	assert_eq!(
+		mint(&mut context, &tokenizer.principal_mint).await.supply,
+		0
+	);
+	assert_eq!(mint(&mut context, &tokenizer.yield_mint).await.supply, 0);
+}
+
+#[tokio::test]
+async fn tokenize_requires_the_users_signature() {
+	let (mut context, tokenizer, user) = deposited(1_000).await;
+	let address = user.pubkey();
+
+	for mut instruction in [
+		tokenizer.tokenize_principal(&address, 1_000),
+		tokenizer.tokenize_yield(&address, 1_000),
+	] {
+		instruction.accounts[2].is_signer = false;
+		let result = send(&mut context, &[instruction], &[]).await;
+		assert_error(result, InstructionError::MissingRequiredSignature);
+	}
+}
+
+#[tokio::test]
+async fn untokenized_deposits_are_not_claimable_as_yield() {
+	let (mut context, tokenizer, _) = deposited(1_000).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+	let address = user.pubkey();
+
+	send(
+		&mut context,
+		&[
+			tokenizer.deposit_and_tokenize(&address, 1_000),
+			tokenizer.claim_yield(&address, 1_000, false),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		0
+	);
+	assert_eq!(tokenizer.vault_balance(&mut context).await, 2_000);
+}
+
+#[tokio::test]
+async fn tokenize_after_expiry_but_not_deposit() {
+	let (mut context, tokenizer, user) = deposited(1_000).await;
+	let address = user.pubkey();
+	mint_to(&mut context, &tokenizer.underlying_mint, &address, 1_000).await;
+	warp_to(&mut context, tokenizer.expiry_date + 1).await;
+
+	let result = send(
+		&mut context,
+		&[tokenizer.deposit_underlying(&address, 1_000)],
+		&[&user],
+	)
+	.await;
+	assert_error(result, tokenizer_error(TokenizerError::ExpiryDateElapsed));
+
+	// Underlying deposited before expiry is still tokenized and redeemed afterwards
+	send(
+		&mut context,
+		&[
+			tokenizer.tokenize_principal(&address, 1_000),
+			tokenizer.redeem_mature_principal(&address, 1_000),
+		],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		2_000
+	);
+}
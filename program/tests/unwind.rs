@@ -0,0 +1,117 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{error::TokenizerError, exchange_rate, mul_div_floor, Expiry, RATE_SCALE},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::signature::{Keypair, Signer},
+};
+
+const FIXED_APY: u64 = 1_000;
+
+// A 12 month tokenizer at a 10% fixed APY holding one user's tokenized 1,000 deposit and 100
+// of yield, half way to expiry
+async fn half_way() -> (ProgramTestContext, Tokenizer, Keypair) {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, FIXED_APY).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&user.pubkey(), 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	tokenizer.accrue(&mut context, 100).await;
+	warp_to(&mut context, (START + tokenizer.expiry_date) / 2).await;
+
+	(context, tokenizer, user)
+}
+
+// Underlying paid for unwinding `amount` principal from `half_way`
+fn discounted(tokenizer: &Tokenizer, amount: u64) -> u64 {
+	let rate = exchange_rate(
+		FIXED_APY,
+		tokenizer.expiry_date,
+		(START + tokenizer.expiry_date) / 2,
+	)
+	.unwrap();
+
+	mul_div_floor(amount, rate, RATE_SCALE).unwrap()
+}
+
+#[tokio::test]
+async fn unwind_a_quarter() {
+	let (mut context, tokenizer, user) = half_way().await;
+	let address = user.pubkey();
+	let principal_payout = discounted(&tokenizer, 250);
+	assert!(principal_payout < 250);
+
+	send(
+		&mut context,
+		&[tokenizer.unwind(&address, 2_500, 100 + principal_payout)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		100 + principal_payout
+	);
+	assert_eq!(
+		tokenizer.principal_balance(&mut context, &address).await,
+		750
+	);
+	assert_eq!(tokenizer.yield_balance(&mut context, &address).await, 750);
+	// The discount is left as surplus for the remaining yield
+	assert_eq!(
+		tokenizer.vault_balance(&mut context).await,
+		1_000 - principal_payout
+	);
+}
+
+#[tokio::test]
+async fn unwind_everything() {
+	let (mut context, tokenizer, user) = half_way().await;
+	let address = user.pubkey();
+	let principal_payout = discounted(&tokenizer, 1_000);
+
+	send(
+		&mut context,
+		&[tokenizer.unwind(&address, 10_000, 100 + principal_payout)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		tokenizer.underlying_balance(&mut context, &address).await,
+		100 + principal_payout
+	);
+	assert_eq!(tokenizer.principal_balance(&mut context, &address).await, 0);
+	assert_eq!(tokenizer.yield_balance(&mut context, &address).await, 0);
+	assert_eq!(
+		mint(&mut context, &tokenizer.principal_mint).await.supply,
+		0
+	);
+	assert_eq!(mint(&mut context, &tokenizer.yield_mint).await.supply, 0);
+}
+
+#[tokio::test]
+async fn unwind_rejects_slippage() {
+	let (mut context, tokenizer, user) = half_way().await;
+	let principal_payout = discounted(&tokenizer, 250);
+
+	let result = send(
+		&mut context,
+		&[tokenizer.unwind(&user.pubkey(), 2_500, 101 + principal_payout)],
+		&[&user],
+	)
+	.await;
+	assert_error(result, tokenizer_error(TokenizerError::SlippageExceeded));
+}
@@ -0,0 +1,112 @@
+// A frozen build rejects new tokenizers
+#![cfg(not(feature = "frozen"))]
+
+mod common;
+
+use {
+	common::*,
+	sclr_token::{
+		error::TokenizerError,
+		instruction::{self, RedeemOrder},
+		Expiry,
+	},
+	solana_program_test::ProgramTestContext,
+	solana_sdk::{instruction::Instruction, signature::Signer},
+};
+
+fn update_fixed_apy(tokenizer: &Tokenizer, fixed_apy: u64) -> Instruction {
+	instruction::update_fixed_apy(
+		&tokenizer.address,
+		&tokenizer.authority,
+		&tokenizer.principal_mint,
+		&tokenizer.yield_mint,
+		fixed_apy,
+	)
+	.unwrap()
+}
+
+async fn assert_updates(context: &mut ProgramTestContext, tokenizer: &Tokenizer, fixed_apy: u64) {
+	send(context, &[update_fixed_apy(tokenizer, fixed_apy)], &[])
+		.await
+		.unwrap();
+	send(context, &[tokenizer.update_rebasing(true)], &[])
+		.await
+		.unwrap();
+
+	let state = tokenizer.state(context).await;
+	assert_eq!(state.fixed_apy, fixed_apy);
+	assert!(state.rebasing);
+	assert_eq!(state.expiry_date, tokenizer.expiry_date);
+
+	send(
+		context,
+		&[
+			update_fixed_apy(tokenizer, 0),
+			tokenizer.update_rebasing(false),
+		],
+		&[],
+	)
+	.await
+	.unwrap();
+}
+
+#[tokio::test]
+async fn updates_allowed_without_outstanding_tokens() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+	let address = user.pubkey();
+
+	assert_updates(&mut context, &tokenizer, 500).await;
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&address, 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+	let result = send(
+		&mut context,
+		&[tokenizer.redeem_principal_and_yield(&address, 1_000, 0, RedeemOrder::PrincipalFirst)],
+		&[&user],
+	)
+	.await;
+	result.unwrap();
+
+	// Everything tokenized has been redeemed, so the terms can change again
+	assert_updates(&mut context, &tokenizer, 300).await;
+}
+
+#[tokio::test]
+async fn updates_rejected_with_outstanding_tokens() {
+	let mut context = start().await;
+	let tokenizer = Tokenizer::create(&mut context, Expiry::TwelveMonths, 0).await;
+	let user = create_user(&mut context, &tokenizer.underlying_mint, 1_000).await;
+	let address = user.pubkey();
+
+	send(
+		&mut context,
+		&[tokenizer.deposit_and_tokenize(&address, 1_000)],
+		&[&user],
+	)
+	.await
+	.unwrap();
+
+	let result = send(&mut context, &[update_fixed_apy(&tokenizer, 500)], &[]).await;
+	assert_error(
+		result,
+		tokenizer_error(TokenizerError::TokenizerHasDeposits),
+	);
+
+	let result = send(&mut context, &[tokenizer.update_rebasing(true)], &[]).await;
+	assert_error(
+		result,
+		tokenizer_error(TokenizerError::TokenizerHasDeposits),
+	);
+
+	let state = tokenizer.state(&mut context).await;
+	assert_eq!(state.fixed_apy, 0);
+	assert!(!state.rebasing);
+	assert_eq!(state.expiry_date, tokenizer.expiry_date);
+}